@@ -0,0 +1,665 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+use zip::result::ZipError;
+
+mod nested;
+pub mod safety;
+
+pub use nested::{list_archive, resolve_entry};
+use safety::{ArchiveWarning, LimitedReader, LimitedWriter, Limits};
+
+// Selisih detik antara epoch Windows (1601) dan epoch Unix (1970)
+const NT_UNIX_EPOCH_DIFF_SECS: i64 = 11_644_473_600;
+
+#[derive(Serialize)]
+pub struct ArchiveContent {
+    pub format: Option<ArchiveFormat>,
+    pub files: Vec<ArchiveEntry>,
+    // true jika header atau salah satu entry terenkripsi (butuh password)
+    pub encrypted: bool,
+    pub warnings: Vec<ArchiveWarning>,
+}
+
+impl ArchiveContent {
+    pub fn empty() -> Self {
+        ArchiveContent {
+            format: None,
+            files: vec![],
+            encrypted: false,
+            warnings: vec![],
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct ArchiveEntry {
+    pub name: String,
+    pub size: u64,
+    // None jika format tidak menyimpan ukuran terkompresi per entry (tar.gz, rar, 7z solid)
+    pub compressed_size: Option<u64>,
+    pub modified: Option<DateTime<Utc>>,
+    pub is_dir: bool,
+    // Isi archive di dalam archive, dengan nama relatif terhadap archive tersebut.
+    // None untuk entry biasa atau jika batas kedalaman tercapai.
+    pub children: Option<Vec<ArchiveEntry>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveFormat {
+    Zip,
+    Rar,
+    Tar,
+    TarGz,
+    TarXz,
+    SevenZ,
+}
+
+#[derive(Debug)]
+pub enum ArchiveError {
+    Unsupported,
+    EntryNotFound,
+    Encrypted,
+    UnsafeName,
+    LimitExceeded(String),
+    Io(io::Error),
+    Corrupt(String),
+}
+
+impl std::fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArchiveError::Unsupported => write!(f, "format archive tidak didukung"),
+            ArchiveError::EntryNotFound => write!(f, "entry tidak ditemukan"),
+            ArchiveError::Encrypted => write!(f, "entry terenkripsi"),
+            ArchiveError::UnsafeName => write!(f, "nama entry tidak aman"),
+            ArchiveError::LimitExceeded(reason) => write!(f, "batas keamanan terlampaui: {}", reason),
+            ArchiveError::Io(e) => write!(f, "gagal membuka archive: {}", e),
+            ArchiveError::Corrupt(e) => write!(f, "archive rusak: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ArchiveError {}
+
+impl From<io::Error> for ArchiveError {
+    fn from(e: io::Error) -> Self {
+        ArchiveError::Io(e)
+    }
+}
+
+// Satu implementasi per format; dipilih dari hasil `detect_format`
+pub trait ArchiveReader {
+    // Berhenti setelah `max_entries` entry agar archive dengan jutaan entry tidak menghabiskan memori
+    fn list(&self, path: &Path, max_entries: usize) -> Result<ArchiveContent, ArchiveError>;
+    // Tulis isi satu file di dalam archive (nama sama seperti hasil `list`) ke `out`.
+    // Entry yang lebih besar dari `limit` byte ditolak sebelum didekompresi
+    // jika ukurannya diketahui, dan ekstraksi berhenti begitu `limit` terlewati.
+    fn extract(&self, path: &Path, name: &str, out: &mut dyn Write, limit: u64) -> Result<u64, ArchiveError>;
+}
+
+// === Format Detection ===
+
+fn starts_with_any(header: &[u8], signatures: &[&[u8]]) -> bool {
+    signatures.iter().any(|sig| header.starts_with(sig))
+}
+
+// Magic bytes di awal file. Untuk gzip/xz isi stream didekompresi sebagian
+// untuk memastikan di dalamnya memang tar, bukan file tunggal terkompresi.
+pub fn detect_format(path: &Path) -> io::Result<Option<ArchiveFormat>> {
+    let mut header = Vec::with_capacity(512);
+    File::open(path)?.take(512).read_to_end(&mut header)?;
+
+    if starts_with_any(&header, &[b"PK\x03\x04", b"PK\x05\x06", b"PK\x07\x08"]) {
+        return Ok(Some(ArchiveFormat::Zip));
+    }
+    if header.starts_with(b"Rar!\x1a\x07") {
+        return Ok(Some(ArchiveFormat::Rar));
+    }
+    if header.starts_with(b"7z\xbc\xaf\x27\x1c") {
+        return Ok(Some(ArchiveFormat::SevenZ));
+    }
+    if is_tar_header(&header) {
+        return Ok(Some(ArchiveFormat::Tar));
+    }
+    if header.starts_with(&[0x1f, 0x8b]) {
+        let decoder = flate2::read::GzDecoder::new(File::open(path)?);
+        return Ok(starts_with_tar(decoder).then_some(ArchiveFormat::TarGz));
+    }
+    if header.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+        let decoder = xz2::read::XzDecoder::new(File::open(path)?);
+        return Ok(starts_with_tar(decoder).then_some(ArchiveFormat::TarXz));
+    }
+    Ok(None)
+}
+
+// Header ustar (POSIX/GNU) menyimpan magic "ustar" di offset 257
+fn is_tar_header(header: &[u8]) -> bool {
+    header.get(257..262) == Some(b"ustar".as_slice())
+}
+
+fn starts_with_tar(reader: impl Read) -> bool {
+    let mut header = Vec::with_capacity(512);
+    reader.take(512).read_to_end(&mut header).is_ok() && is_tar_header(&header)
+}
+
+// `max_decoded` membatasi total byte yang didekompresi dalam satu operasi,
+// termasuk entry yang hanya dilewati, untuk format yang harus dibaca berurutan
+pub fn reader_for(format: ArchiveFormat, max_decoded: u64) -> Box<dyn ArchiveReader> {
+    match format {
+        ArchiveFormat::Zip => Box::new(ZipReader),
+        ArchiveFormat::Rar => Box::new(RarReader),
+        ArchiveFormat::Tar | ArchiveFormat::TarGz | ArchiveFormat::TarXz => Box::new(TarReader { format, max_decoded }),
+        ArchiveFormat::SevenZ => Box::new(SevenZReader { max_decoded }),
+    }
+}
+
+// Listing satu level archive, selalu melewati pemeriksaan safety; entry
+// berbahaya dibuang dan diganti peringatan di `ArchiveContent.warnings`
+fn list_level(path: &Path, max_entries: usize, limits: &Limits) -> Result<ArchiveContent, ArchiveError> {
+    let format = detect_format(path)?.ok_or(ArchiveError::Unsupported)?;
+    // Satu entry lebih dari batas supaya `inspect` tahu daftarnya terpotong
+    let mut content = reader_for(format, limits.max_total_size).list(path, max_entries.saturating_add(1))?;
+    content.format = Some(format);
+    safety::inspect(&mut content, std::fs::metadata(path)?.len(), max_entries, limits);
+    Ok(content)
+}
+
+fn find_entry<'a>(content: &'a ArchiveContent, name: &str) -> Result<&'a ArchiveEntry, ArchiveError> {
+    if !safety::is_safe_name(name) {
+        return Err(ArchiveError::UnsafeName);
+    }
+    content
+        .files
+        .iter()
+        .find(|e| !e.is_dir && e.name == name)
+        .ok_or(ArchiveError::EntryNotFound)
+}
+
+fn extract_limited(
+    path: &Path,
+    entry: &ArchiveEntry,
+    out: &mut dyn Write,
+    limit: u64,
+    limits: &Limits,
+) -> Result<u64, ArchiveError> {
+    let format = detect_format(path)?.ok_or(ArchiveError::Unsupported)?;
+    let mut writer = LimitedWriter::new(out, limit);
+    match reader_for(format, limits.max_total_size).extract(path, &entry.name, &mut writer, limit) {
+        Err(_) if writer.exceeded => Err(entry_too_large()),
+        result => result,
+    }
+}
+
+fn entry_too_large() -> ArchiveError {
+    ArchiveError::LimitExceeded("isi entry melebihi batas".to_string())
+}
+
+fn decoded_too_large() -> ArchiveError {
+    ArchiveError::LimitExceeded("data archive yang didekompresi melebihi batas".to_string())
+}
+
+// Salin isi entry ke `out`; error jika lebih dari `limit` byte
+fn copy_limited(reader: &mut dyn Read, out: &mut dyn Write, limit: u64) -> Result<u64, ArchiveError> {
+    let written = io::copy(&mut reader.take(limit.saturating_add(1)), out)?;
+    if written > limit {
+        return Err(entry_too_large());
+    }
+    Ok(written)
+}
+
+// `entry` harus berasal dari listing archive yang sama (lihat `resolve_entry`)
+pub fn extract_entry(path: &Path, entry: &ArchiveEntry, out: &mut dyn Write) -> Result<u64, ArchiveError> {
+    let limits = Limits::from_settings();
+    let archive_size = std::fs::metadata(path)?.len();
+    safety::check_entry(entry, archive_size, &limits)?;
+    let limit = safety::output_limit(entry, archive_size, &limits);
+    extract_limited(path, entry, out, limit, &limits)
+}
+
+// Baca satu entry ke memori. None jika isinya lebih besar dari `limit`.
+pub fn read_entry(path: &Path, entry: &ArchiveEntry, limit: u64) -> Result<Option<Vec<u8>>, ArchiveError> {
+    let limits = Limits::from_settings();
+    let archive_size = std::fs::metadata(path)?.len();
+    safety::check_entry(entry, archive_size, &limits)?;
+    let output_limit = safety::output_limit(entry, archive_size, &limits);
+
+    let mut data = Vec::new();
+    match extract_limited(path, entry, &mut data, limit.min(output_limit), &limits) {
+        Ok(_) => Ok(Some(data)),
+        Err(ArchiveError::LimitExceeded(_)) if limit < output_limit => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// File sementara di temp dir OS, dihapus otomatis saat di-drop
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn new() -> Self {
+        TempFile(std::env::temp_dir().join(format!("lomba-archive-{}", Uuid::new_v4())))
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        std::fs::remove_file(&self.0).ok();
+    }
+}
+
+// Tanggal/jam format DOS (dipakai zip dan rar), tanpa zona waktu
+fn dos_datetime(date: u16, time: u16) -> Option<DateTime<Utc>> {
+    NaiveDate::from_ymd_opt(
+        1980 + (date >> 9) as i32,
+        ((date >> 5) & 0x0f) as u32,
+        (date & 0x1f) as u32,
+    )?
+    .and_hms_opt(
+        (time >> 11) as u32,
+        ((time >> 5) & 0x3f) as u32,
+        ((time & 0x1f) * 2) as u32,
+    )
+    .map(|dt| dt.and_utc())
+}
+
+// === Readers ===
+
+struct ZipReader;
+
+impl ArchiveReader for ZipReader {
+    fn list(&self, path: &Path, max_entries: usize) -> Result<ArchiveContent, ArchiveError> {
+        let mut archive = zip::ZipArchive::new(File::open(path)?)
+            .map_err(|e| ArchiveError::Corrupt(e.to_string()))?;
+
+        let mut content = ArchiveContent::empty();
+        for i in 0..archive.len().min(max_entries) {
+            // by_index_raw tidak butuh password, jadi entry terenkripsi tetap terdaftar
+            if let Ok(file) = archive.by_index_raw(i) {
+                content.encrypted |= file.encrypted();
+                content.files.push(ArchiveEntry {
+                    name: file.name().to_string(),
+                    size: file.size(),
+                    compressed_size: Some(file.compressed_size()),
+                    modified: file
+                        .last_modified()
+                        .and_then(|dt| dos_datetime(dt.datepart(), dt.timepart())),
+                    is_dir: file.is_dir(),
+                    children: None,
+                });
+            }
+        }
+        Ok(content)
+    }
+
+    fn extract(&self, path: &Path, name: &str, out: &mut dyn Write, limit: u64) -> Result<u64, ArchiveError> {
+        let mut archive = zip::ZipArchive::new(File::open(path)?)
+            .map_err(|e| ArchiveError::Corrupt(e.to_string()))?;
+        let mut file = match archive.by_name(name) {
+            Ok(file) if !file.is_dir() => file,
+            Ok(_) | Err(ZipError::FileNotFound) => return Err(ArchiveError::EntryNotFound),
+            Err(ZipError::UnsupportedArchive(msg)) if msg == ZipError::PASSWORD_REQUIRED => {
+                return Err(ArchiveError::Encrypted);
+            }
+            Err(e) => return Err(ArchiveError::Corrupt(e.to_string())),
+        };
+        if file.size() > limit {
+            return Err(entry_too_large());
+        }
+        copy_limited(&mut file, out, limit)
+    }
+}
+
+// RAR4/RAR5 lewat unrar. Untuk archive multi-volume, listing dimulai dari
+// part pertama di folder yang sama agar entry yang terpecah tetap muncul sekali.
+struct RarReader;
+
+impl RarReader {
+    fn open(path: &Path) -> unrar::Archive<'_> {
+        if unrar::Archive::new(path).is_multipart() {
+            unrar::Archive::new(path).as_first_part()
+        } else {
+            unrar::Archive::new(path)
+        }
+    }
+
+    fn entry_name(header: &unrar::FileHeader) -> String {
+        let mut name = header.filename.to_string_lossy().replace('\\', "/");
+        if header.is_directory() && !name.ends_with('/') {
+            name.push('/');
+        }
+        name
+    }
+}
+
+impl ArchiveReader for RarReader {
+    fn list(&self, path: &Path, max_entries: usize) -> Result<ArchiveContent, ArchiveError> {
+        let listing = match Self::open(path).open_for_listing() {
+            Ok(listing) => listing,
+            Err(e) if e.code == unrar::error::Code::MissingPassword => {
+                return Ok(ArchiveContent {
+                    encrypted: true,
+                    ..ArchiveContent::empty()
+                });
+            }
+            Err(e) => return Err(ArchiveError::Corrupt(e.to_string())),
+        };
+
+        let mut content = ArchiveContent {
+            encrypted: listing.has_encrypted_headers(),
+            ..ArchiveContent::empty()
+        };
+        for header in listing.flatten().take(max_entries) {
+            content.encrypted |= header.is_encrypted();
+            content.files.push(ArchiveEntry {
+                name: Self::entry_name(&header),
+                size: header.unpacked_size,
+                compressed_size: None,
+                modified: dos_datetime((header.file_time >> 16) as u16, header.file_time as u16),
+                is_dir: header.is_directory(),
+                children: None,
+            });
+        }
+        Ok(content)
+    }
+
+    // unrar hanya bisa mengekstrak ke file, jadi entry ditulis ke file
+    // sementara lalu disalin ke `out`. Decoder unrar berhenti di ukuran yang
+    // tertulis di header, jadi ukuran itu dicek dulu supaya file sementara
+    // tidak bisa melebihi `limit`.
+    fn extract(&self, path: &Path, name: &str, out: &mut dyn Write, limit: u64) -> Result<u64, ArchiveError> {
+        let mut archive = match Self::open(path).open_for_processing() {
+            Ok(archive) => archive,
+            Err(e) if e.code == unrar::error::Code::MissingPassword => {
+                return Err(ArchiveError::Encrypted);
+            }
+            Err(e) => return Err(ArchiveError::Corrupt(e.to_string())),
+        };
+        let corrupt = |e: unrar::error::UnrarError| ArchiveError::Corrupt(e.to_string());
+
+        while let Some(header) = archive.read_header().map_err(corrupt)? {
+            let entry = header.entry();
+            if entry.is_directory() || Self::entry_name(entry) != name {
+                archive = header.skip().map_err(corrupt)?;
+                continue;
+            }
+            if entry.is_encrypted() {
+                return Err(ArchiveError::Encrypted);
+            }
+
+            if entry.unpacked_size > limit {
+                return Err(entry_too_large());
+            }
+
+            let temp = TempFile::new();
+            header.extract_to(&temp.0).map_err(corrupt)?;
+            let file = File::open(&temp.0)?;
+            if file.metadata()?.len() > limit {
+                return Err(entry_too_large());
+            }
+            return copy_limited(&mut BufReader::new(file), out, limit);
+        }
+        Err(ArchiveError::EntryNotFound)
+    }
+}
+
+// Tar biasa, tar.gz dan tar.xz. Karena kompresi berlaku untuk seluruh stream,
+// ukuran terkompresi per entry hanya diketahui untuk tar tanpa kompresi.
+// Entry hanya bisa dilewati dengan membaca seluruh isinya, jadi stream
+// dibatasi `max_decoded` byte.
+struct TarReader {
+    format: ArchiveFormat,
+    max_decoded: u64,
+}
+
+impl TarReader {
+    fn entry_name<R: Read>(entry: &tar::Entry<R>) -> String {
+        let mut name = entry
+            .path()
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        if entry.header().entry_type().is_dir() && !name.ends_with('/') {
+            name.push('/');
+        }
+        name
+    }
+
+    fn open(&self, path: &Path) -> io::Result<tar::Archive<LimitedReader<Box<dyn Read>>>> {
+        let file = BufReader::new(File::open(path)?);
+        let stream: Box<dyn Read> = match self.format {
+            ArchiveFormat::TarGz => Box::new(flate2::read::GzDecoder::new(file)),
+            ArchiveFormat::TarXz => Box::new(xz2::read::XzDecoder::new(file)),
+            _ => Box::new(file),
+        };
+        Ok(tar::Archive::new(LimitedReader::new(stream, self.max_decoded)))
+    }
+
+    fn list_entries<R: Read>(&self, archive: &mut tar::Archive<R>, max_entries: usize) -> Result<ArchiveContent, ArchiveError> {
+        let entries = archive
+            .entries()
+            .map_err(|e| ArchiveError::Corrupt(e.to_string()))?;
+
+        let mut content = ArchiveContent::empty();
+        for entry in entries.take(max_entries) {
+            let entry = entry.map_err(|e| ArchiveError::Corrupt(e.to_string()))?;
+            let header = entry.header();
+            let is_dir = header.entry_type().is_dir();
+            let size = header.size().unwrap_or(0);
+            content.files.push(ArchiveEntry {
+                name: Self::entry_name(&entry),
+                size,
+                compressed_size: (self.format == ArchiveFormat::Tar).then_some(size),
+                modified: header
+                    .mtime()
+                    .ok()
+                    .and_then(|secs| DateTime::from_timestamp(secs as i64, 0)),
+                is_dir,
+                children: None,
+            });
+        }
+        Ok(content)
+    }
+
+    fn extract_entry<R: Read>(
+        archive: &mut tar::Archive<R>,
+        name: &str,
+        out: &mut dyn Write,
+        limit: u64,
+    ) -> Result<u64, ArchiveError> {
+        let entries = archive
+            .entries()
+            .map_err(|e| ArchiveError::Corrupt(e.to_string()))?;
+
+        for entry in entries {
+            let mut entry = entry.map_err(|e| ArchiveError::Corrupt(e.to_string()))?;
+            if entry.header().entry_type().is_file() && Self::entry_name(&entry) == name {
+                if entry.size() > limit {
+                    return Err(entry_too_large());
+                }
+                return copy_limited(&mut entry, out, limit);
+            }
+        }
+        Err(ArchiveError::EntryNotFound)
+    }
+}
+
+impl ArchiveReader for TarReader {
+    fn list(&self, path: &Path, max_entries: usize) -> Result<ArchiveContent, ArchiveError> {
+        let mut archive = self.open(path)?;
+        let result = self.list_entries(&mut archive, max_entries);
+        if archive.into_inner().exceeded {
+            return Err(decoded_too_large());
+        }
+        result
+    }
+
+    fn extract(&self, path: &Path, name: &str, out: &mut dyn Write, limit: u64) -> Result<u64, ArchiveError> {
+        let mut archive = self.open(path)?;
+        let result = Self::extract_entry(&mut archive, name, out, limit);
+        if archive.into_inner().exceeded {
+            return Err(decoded_too_large());
+        }
+        result
+    }
+}
+
+struct SevenZReader {
+    max_decoded: u64,
+}
+
+impl ArchiveReader for SevenZReader {
+    fn list(&self, path: &Path, max_entries: usize) -> Result<ArchiveContent, ArchiveError> {
+        let archive = match sevenz_rust::Archive::open(path) {
+            Ok(archive) => archive,
+            // Header terenkripsi: daftar file tidak bisa dibaca tanpa password
+            Err(sevenz_rust::Error::PasswordRequired) => {
+                return Ok(ArchiveContent {
+                    encrypted: true,
+                    ..ArchiveContent::empty()
+                });
+            }
+            Err(e) => return Err(ArchiveError::Corrupt(e.to_string())),
+        };
+
+        let encrypted = archive.folders.iter().any(|folder| {
+            folder
+                .coders
+                .iter()
+                .any(|c| c.decompression_method_id() == sevenz_rust::SevenZMethod::ID_AES256SHA256)
+        });
+        let files = archive
+            .files
+            .iter()
+            .take(max_entries)
+            .map(|entry| {
+                let mut name = entry.name.clone();
+                if entry.is_directory && !name.ends_with('/') {
+                    name.push('/');
+                }
+                ArchiveEntry {
+                    name,
+                    size: entry.size,
+                    // Pada blok solid hanya entry pertama yang membawa ukuran terkompresi
+                    compressed_size: (entry.has_stream && entry.compressed_size > 0)
+                        .then_some(entry.compressed_size),
+                    modified: entry
+                        .has_last_modified_date
+                        .then(|| {
+                            let secs = (entry.last_modified_date.to_raw() / 10_000_000) as i64;
+                            DateTime::from_timestamp(secs - NT_UNIX_EPOCH_DIFF_SECS, 0)
+                        })
+                        .flatten(),
+                    is_dir: entry.is_directory,
+                    children: None,
+                }
+            })
+            .collect();
+
+        Ok(ArchiveContent {
+            files,
+            encrypted,
+            ..ArchiveContent::empty()
+        })
+    }
+
+    // Entry di blok solid hanya bisa dibaca berurutan, jadi entry sebelumnya
+    // tetap didekompresi (dibuang ke sink) sampai entry yang dicari, dengan
+    // total dibatasi `max_decoded` byte
+    fn extract(&self, path: &Path, name: &str, out: &mut dyn Write, limit: u64) -> Result<u64, ArchiveError> {
+        let mut reader = match sevenz_rust::SevenZReader::open(path, sevenz_rust::Password::empty()) {
+            Ok(reader) => reader,
+            Err(sevenz_rust::Error::PasswordRequired) => return Err(ArchiveError::Encrypted),
+            Err(e) => return Err(ArchiveError::Corrupt(e.to_string())),
+        };
+
+        let mut written = None;
+        let mut failure = None;
+        let mut skipped: u64 = 0;
+        let result = reader.for_each_entries(|entry, data| {
+            if written.is_some() || failure.is_some() {
+                return Ok(false);
+            }
+            if entry.is_directory || entry.name != name {
+                let budget = self.max_decoded.saturating_sub(skipped);
+                skipped += io::copy(&mut data.take(budget.saturating_add(1)), &mut io::sink())?;
+                if skipped > self.max_decoded {
+                    failure = Some(decoded_too_large());
+                    return Ok(false);
+                }
+                return Ok(true);
+            }
+            if entry.size > limit {
+                failure = Some(entry_too_large());
+                return Ok(false);
+            }
+            match copy_limited(data, out, limit) {
+                Ok(n) => written = Some(n),
+                Err(e) => failure = Some(e),
+            }
+            Ok(false)
+        });
+
+        if let Some(e) = failure {
+            return Err(e);
+        }
+        match result {
+            Ok(()) => written.ok_or(ArchiveError::EntryNotFound),
+            Err(sevenz_rust::Error::PasswordRequired | sevenz_rust::Error::MaybeBadPassword(_)) => {
+                Err(ArchiveError::Encrypted)
+            }
+            // Tanpa dukungan AES, isi terenkripsi dilaporkan sebagai metode yang tidak didukung
+            Err(sevenz_rust::Error::UnsupportedCompressionMethod(method))
+                if method == sevenz_rust::SevenZMethod::AES256SHA256.name() =>
+            {
+                Err(ArchiveError::Encrypted)
+            }
+            Err(e) => Err(ArchiveError::Corrupt(e.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // tar.gz berisi `entries` file masing-masing `size` byte nol
+    fn tar_gz(entries: usize, size: usize) -> TempFile {
+        let temp = TempFile::new();
+        let encoder = flate2::write::GzEncoder::new(File::create(&temp.0).unwrap(), flate2::Compression::best());
+        let mut builder = tar::Builder::new(encoder);
+        for i in 0..entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(size as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, format!("f{}.txt", i), &vec![0u8; size][..]).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+        temp
+    }
+
+    #[test]
+    fn tar_listing_stops_at_decoded_limit() {
+        let temp = tar_gz(4, 64 * 1024);
+        let reader = TarReader { format: ArchiveFormat::TarGz, max_decoded: 1024 * 1024 };
+        assert_eq!(reader.list(&temp.0, 10).unwrap().files.len(), 4);
+
+        let reader = TarReader { format: ArchiveFormat::TarGz, max_decoded: 100 * 1024 };
+        assert!(matches!(reader.list(&temp.0, 10), Err(ArchiveError::LimitExceeded(_))));
+    }
+
+    #[test]
+    fn tar_extract_honors_limit() {
+        let temp = tar_gz(2, 1024);
+        let reader = TarReader { format: ArchiveFormat::TarGz, max_decoded: 1024 * 1024 };
+        let mut out = Vec::new();
+        assert_eq!(reader.extract(&temp.0, "f1.txt", &mut out, 1024).unwrap(), 1024);
+        assert!(matches!(
+            reader.extract(&temp.0, "f1.txt", &mut Vec::new(), 1023),
+            Err(ArchiveError::LimitExceeded(_))
+        ));
+    }
+}
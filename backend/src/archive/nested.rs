@@ -0,0 +1,173 @@
+use super::safety::{self, ArchiveWarning, Limits, WarningKind};
+use super::{detect_format, extract_entry, extract_limited, find_entry, list_level, ArchiveContent, ArchiveEntry, ArchiveError, TempFile};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+// Pemisah archive di dalam archive, mis. `build.zip!/src/a.c`
+pub const NESTED_SEPARATOR: &str = "!/";
+
+// Kandidat dipilih dari ekstensi agar tidak semua entry diekstrak;
+// formatnya tetap dipastikan lewat magic bytes
+const ARCHIVE_EXTENSIONS: [&str; 8] = [".zip", ".rar", ".7z", ".tar", ".tar.gz", ".tgz", ".tar.xz", ".txz"];
+
+fn looks_like_archive(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    ARCHIVE_EXTENSIONS.iter().any(|ext| lower.ends_with(ext))
+}
+
+fn nested_name(archive: &str, entry: Option<String>) -> String {
+    match entry {
+        Some(entry) => format!("{}{}{}", archive, NESTED_SEPARATOR, entry),
+        None => archive.to_string(),
+    }
+}
+
+// Jatah entry dan byte hasil ekstraksi dipakai bersama oleh semua level,
+// supaya archive bertingkat tidak bisa melipatgandakan batas safety
+struct Walk<'a> {
+    limits: &'a Limits,
+    entries_left: usize,
+    bytes_left: u64,
+}
+
+// Listing archive beserta isi archive di dalamnya (sebagai `children`)
+// sampai kedalaman `archive_max_nesting`
+pub fn list_archive(path: &Path) -> Result<ArchiveContent, ArchiveError> {
+    let limits = Limits::from_settings();
+    let mut walk = Walk {
+        limits: &limits,
+        entries_left: limits.max_entries,
+        bytes_left: limits.max_total_size,
+    };
+    walk.list(path, 0)
+}
+
+impl Walk<'_> {
+    fn list(&mut self, path: &Path, depth: usize) -> Result<ArchiveContent, ArchiveError> {
+        let mut content = list_level(path, self.entries_left, self.limits)?;
+        self.entries_left -= content.files.len();
+        let archive_size = std::fs::metadata(path)?.len();
+
+        let mut warnings = vec![];
+        for entry in content.files.iter_mut() {
+            if entry.is_dir || !looks_like_archive(&entry.name) || self.limits.max_nesting == 0 {
+                continue;
+            }
+            if depth >= self.limits.max_nesting {
+                warnings.push(ArchiveWarning {
+                    kind: WarningKind::NestingTooDeep,
+                    entry: Some(entry.name.clone()),
+                    message: format!("Archive bertingkat lebih dari {} level tidak dibuka", self.limits.max_nesting),
+                });
+                continue;
+            }
+            if self.entries_left == 0 {
+                break;
+            }
+
+            match self.list_inner(path, entry, archive_size, depth) {
+                Ok(inner) => {
+                    content.encrypted |= inner.encrypted;
+                    for mut warning in inner.warnings {
+                        warning.entry = Some(nested_name(&entry.name, warning.entry));
+                        warnings.push(warning);
+                    }
+                    entry.children = Some(inner.files);
+                }
+                // Berekstensi archive tapi isinya bukan archive: tampil sebagai file biasa
+                Err(ArchiveError::Unsupported) => {}
+                Err(e) => warnings.push(ArchiveWarning {
+                    kind: WarningKind::NestedUnreadable,
+                    entry: Some(entry.name.clone()),
+                    message: format!("Archive di dalam tidak bisa dibuka: {}", e),
+                }),
+            }
+        }
+        content.warnings.append(&mut warnings);
+        Ok(content)
+    }
+
+    fn list_inner(
+        &mut self,
+        path: &Path,
+        entry: &ArchiveEntry,
+        archive_size: u64,
+        depth: usize,
+    ) -> Result<ArchiveContent, ArchiveError> {
+        safety::check_entry(entry, archive_size, self.limits)?;
+        let limit = safety::output_limit(entry, archive_size, self.limits).min(self.bytes_left);
+        let temp = TempFile::new();
+        let written = extract_limited(path, entry, &mut File::create(&temp.0)?, limit, self.limits)?;
+        self.bytes_left -= written;
+        self.list(&temp.0, depth + 1)
+    }
+}
+
+pub struct ResolvedEntry {
+    // Archive yang langsung berisi entry; untuk nama bertingkat ini file sementara
+    pub path: PathBuf,
+    pub entry: ArchiveEntry,
+    // Archive perantara yang sudah diekstrak, dihapus saat ResolvedEntry di-drop
+    _temps: Vec<TempFile>,
+}
+
+// Cari entry dari nama bertingkat seperti `inner.zip!/src/a.c`. Nama entry
+// boleh mengandung `!/`, jadi nama hanya dipecah di posisi yang awalannya
+// memang entry archive di level itu. Setiap archive perantara diekstrak ke
+// file sementara dengan batas safety yang sama.
+pub fn resolve_entry(path: &Path, name: &str) -> Result<ResolvedEntry, ArchiveError> {
+    let limits = Limits::from_settings();
+    let mut temps = vec![];
+    let mut current = path.to_path_buf();
+    let mut rest = name;
+
+    loop {
+        let content = list_level(&current, limits.max_entries, &limits)?;
+        // Nama persis selalu didahulukan daripada memecah di `!/`
+        match find_entry(&content, rest) {
+            Ok(entry) => {
+                return Ok(ResolvedEntry {
+                    entry: entry.clone(),
+                    path: current,
+                    _temps: temps,
+                });
+            }
+            Err(ArchiveError::EntryNotFound) if rest.contains(NESTED_SEPARATOR) => {}
+            Err(e) => return Err(e),
+        }
+        if temps.len() >= limits.max_nesting {
+            return Err(ArchiveError::LimitExceeded("archive bertingkat terlalu dalam".to_string()));
+        }
+
+        let Some((inner, remainder)) = open_inner(&current, &content, rest)? else {
+            return Err(ArchiveError::EntryNotFound);
+        };
+        current = inner.0.clone();
+        temps.push(inner);
+        rest = remainder;
+    }
+}
+
+// Awalan pertama dari `name` (dipotong di `!/`) yang merupakan entry archive
+// di `content`; entry berekstensi archive yang isinya bukan archive dilewati
+fn open_inner<'n>(
+    path: &Path,
+    content: &ArchiveContent,
+    name: &'n str,
+) -> Result<Option<(TempFile, &'n str)>, ArchiveError> {
+    for (split, _) in name.match_indices(NESTED_SEPARATOR) {
+        let prefix = &name[..split];
+        if !looks_like_archive(prefix) {
+            continue;
+        }
+        let Some(entry) = content.files.iter().find(|e| !e.is_dir && e.name == prefix) else {
+            continue;
+        };
+        let temp = TempFile::new();
+        extract_entry(path, entry, &mut File::create(&temp.0)?)?;
+        if detect_format(&temp.0)?.is_some() {
+            return Ok(Some((temp, &name[split + NESTED_SEPARATOR.len()..])));
+        }
+    }
+    Ok(None)
+}
@@ -0,0 +1,339 @@
+use super::{ArchiveContent, ArchiveEntry, ArchiveError};
+use crate::config::settings;
+use serde::Serialize;
+use std::io::{self, Read, Write};
+
+pub struct Limits {
+    pub max_total_size: u64,
+    pub max_entries: usize,
+    pub max_ratio: u64,
+    pub max_path_depth: usize,
+    pub max_nesting: usize,
+}
+
+impl Limits {
+    pub fn from_settings() -> Self {
+        let cfg = settings();
+        Limits {
+            max_total_size: cfg.archive_max_total_size,
+            max_entries: cfg.archive_max_entries,
+            max_ratio: cfg.archive_max_ratio,
+            max_path_depth: cfg.archive_max_path_depth,
+            max_nesting: cfg.archive_max_nesting,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WarningKind {
+    TooManyEntries,
+    TotalSizeExceeded,
+    SuspiciousRatio,
+    PathTooDeep,
+    UnsafePath,
+    NestingTooDeep,
+    NestedUnreadable,
+}
+
+#[derive(Serialize)]
+pub struct ArchiveWarning {
+    pub kind: WarningKind,
+    // None jika peringatan berlaku untuk archive secara keseluruhan
+    pub entry: Option<String>,
+    pub message: String,
+}
+
+// Nama absolut (/etc/passwd, C:\...) atau yang mengandung `..` ditolak,
+// walaupun entry tidak pernah ditulis ke disk dengan nama aslinya
+pub fn is_safe_name(name: &str) -> bool {
+    if name.is_empty() || name.contains('\0') || name.starts_with(['/', '\\']) {
+        return false;
+    }
+    let bytes = name.as_bytes();
+    if bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' {
+        return false;
+    }
+    !name.split(['/', '\\']).any(|part| part == "..")
+}
+
+fn path_depth(name: &str) -> usize {
+    name.split(['/', '\\']).filter(|part| !part.is_empty()).count()
+}
+
+fn ratio_exceeded(size: u64, compressed: u64, max_ratio: u64) -> bool {
+    compressed > 0 && size / compressed > max_ratio
+}
+
+// Periksa hasil listing: entry berbahaya dibuang dari daftar, sisanya diberi
+// peringatan. `archive_size` dipakai untuk rasio format yang tidak punya
+// ukuran terkompresi per entry (tar.gz, tar.xz, 7z solid).
+// `max_entries` adalah sisa jatah entry; untuk archive bertingkat jatah ini
+// dipakai bersama oleh semua level.
+pub fn inspect(content: &mut ArchiveContent, archive_size: u64, max_entries: usize, limits: &Limits) {
+    if content.files.len() > max_entries {
+        content.files.truncate(max_entries);
+        content.warnings.push(ArchiveWarning {
+            kind: WarningKind::TooManyEntries,
+            entry: None,
+            message: format!("Jumlah entry melebihi batas {}, sisanya tidak ditampilkan", limits.max_entries),
+        });
+    }
+
+    let mut warnings = vec![];
+    content.files.retain(|entry| {
+        if !is_safe_name(&entry.name) {
+            warnings.push(ArchiveWarning {
+                kind: WarningKind::UnsafePath,
+                entry: Some(entry.name.clone()),
+                message: "Nama entry absolut atau keluar dari folder archive".to_string(),
+            });
+            return false;
+        }
+        if path_depth(&entry.name) > limits.max_path_depth {
+            warnings.push(ArchiveWarning {
+                kind: WarningKind::PathTooDeep,
+                entry: Some(entry.name.clone()),
+                message: format!("Folder bertingkat lebih dari {} level", limits.max_path_depth),
+            });
+            return false;
+        }
+        if entry.compressed_size.is_some_and(|c| ratio_exceeded(entry.size, c, limits.max_ratio)) {
+            warnings.push(ArchiveWarning {
+                kind: WarningKind::SuspiciousRatio,
+                entry: Some(entry.name.clone()),
+                message: format!("Rasio kompresi lebih dari {}:1", limits.max_ratio),
+            });
+        }
+        true
+    });
+    content.warnings.append(&mut warnings);
+
+    let total: u64 = content.files.iter().map(|e| e.size).fold(0, u64::saturating_add);
+    if total > limits.max_total_size {
+        content.warnings.push(ArchiveWarning {
+            kind: WarningKind::TotalSizeExceeded,
+            entry: None,
+            message: format!("Total isi archive melebihi {}MB", limits.max_total_size / (1024 * 1024)),
+        });
+    }
+    if ratio_exceeded(total, archive_size, limits.max_ratio) {
+        content.warnings.push(ArchiveWarning {
+            kind: WarningKind::SuspiciousRatio,
+            entry: None,
+            message: format!("Rasio kompresi archive lebih dari {}:1", limits.max_ratio),
+        });
+    }
+}
+
+// Entry hanya boleh diekstrak jika lolos pemeriksaan yang sama dengan listing
+pub fn check_entry(entry: &ArchiveEntry, archive_size: u64, limits: &Limits) -> Result<(), ArchiveError> {
+    if !is_safe_name(&entry.name) {
+        return Err(ArchiveError::UnsafeName);
+    }
+    if path_depth(&entry.name) > limits.max_path_depth {
+        return Err(ArchiveError::LimitExceeded("folder terlalu dalam".to_string()));
+    }
+    if entry.size > limits.max_total_size {
+        return Err(ArchiveError::LimitExceeded("ukuran entry terlalu besar".to_string()));
+    }
+    if entry.compressed_size.is_some_and(|c| ratio_exceeded(entry.size, c, limits.max_ratio)) {
+        return Err(ArchiveError::LimitExceeded("rasio kompresi mencurigakan".to_string()));
+    }
+    if entry.size > output_limit(entry, archive_size, limits) {
+        return Err(ArchiveError::LimitExceeded("rasio kompresi archive mencurigakan".to_string()));
+    }
+    Ok(())
+}
+
+// Batas byte yang boleh keluar saat ekstraksi. Ukuran di header bisa bohong,
+// jadi batas dihitung dari data terkompresi yang benar-benar ada.
+pub fn output_limit(entry: &ArchiveEntry, archive_size: u64, limits: &Limits) -> u64 {
+    let compressed = entry.compressed_size.filter(|c| *c > 0).unwrap_or(archive_size);
+    compressed
+        .saturating_mul(limits.max_ratio)
+        .min(limits.max_total_size)
+}
+
+// Writer yang menolak data setelah `limit` byte
+pub struct LimitedWriter<'a> {
+    inner: &'a mut dyn Write,
+    written: u64,
+    limit: u64,
+    pub exceeded: bool,
+}
+
+impl<'a> LimitedWriter<'a> {
+    pub fn new(inner: &'a mut dyn Write, limit: u64) -> Self {
+        LimitedWriter {
+            inner,
+            written: 0,
+            limit,
+            exceeded: false,
+        }
+    }
+}
+
+impl Write for LimitedWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.written + buf.len() as u64 > self.limit {
+            self.exceeded = true;
+            return Err(io::Error::other("batas ukuran terlampaui"));
+        }
+        let n = self.inner.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+// Reader yang menolak data setelah `limit` byte. Dipasang di depan decoder
+// supaya entry yang hanya dilewati tetap ikut menghabiskan jatah dekompresi.
+pub struct LimitedReader<R> {
+    inner: R,
+    read: u64,
+    limit: u64,
+    pub exceeded: bool,
+}
+
+impl<R: Read> LimitedReader<R> {
+    pub fn new(inner: R, limit: u64) -> Self {
+        LimitedReader {
+            inner,
+            read: 0,
+            limit,
+            exceeded: false,
+        }
+    }
+}
+
+impl<R: Read> Read for LimitedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.read += n as u64;
+        if self.read > self.limit {
+            self.exceeded = true;
+            return Err(io::Error::other("batas dekompresi terlampaui"));
+        }
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits() -> Limits {
+        Limits {
+            max_total_size: 1024 * 1024,
+            max_entries: 100,
+            max_ratio: 10,
+            max_path_depth: 4,
+            max_nesting: 1,
+        }
+    }
+
+    fn entry(name: &str, size: u64, compressed_size: Option<u64>) -> ArchiveEntry {
+        ArchiveEntry {
+            name: name.to_string(),
+            size,
+            compressed_size,
+            modified: None,
+            is_dir: false,
+            children: None,
+        }
+    }
+
+    #[test]
+    fn rejects_traversal_names() {
+        for name in ["..", "../etc/passwd", "src/../../x", "a\\..\\b", "a/b/.."] {
+            assert!(!is_safe_name(name), "{}", name);
+        }
+        assert!(is_safe_name("src/..hidden/a..b.txt"));
+        assert!(is_safe_name("src/main.rs"));
+    }
+
+    #[test]
+    fn rejects_absolute_and_drive_paths() {
+        for name in ["/etc/passwd", "\\windows\\system32", "C:\\boot.ini", "c:relative", "", "a\0b"] {
+            assert!(!is_safe_name(name), "{:?}", name);
+        }
+    }
+
+    #[test]
+    fn check_entry_rejects_suspicious_ratio() {
+        let limits = limits();
+        assert!(check_entry(&entry("ok.txt", 100, Some(10)), 1000, &limits).is_ok());
+        assert!(matches!(
+            check_entry(&entry("bomb.txt", 1000, Some(10)), 1000, &limits),
+            Err(ArchiveError::LimitExceeded(_))
+        ));
+        // Tanpa ukuran terkompresi per entry, rasio dihitung dari ukuran archive
+        assert!(matches!(
+            check_entry(&entry("solid.txt", 5000, None), 100, &limits),
+            Err(ArchiveError::LimitExceeded(_))
+        ));
+    }
+
+    #[test]
+    fn check_entry_rejects_unsafe_and_oversized() {
+        let limits = limits();
+        assert!(matches!(
+            check_entry(&entry("../x", 1, Some(1)), 1000, &limits),
+            Err(ArchiveError::UnsafeName)
+        ));
+        assert!(matches!(
+            check_entry(&entry("a/b/c/d/e", 1, Some(1)), 1000, &limits),
+            Err(ArchiveError::LimitExceeded(_))
+        ));
+        assert!(matches!(
+            check_entry(&entry("big.bin", 2 * 1024 * 1024, Some(1024 * 1024)), u64::MAX, &limits),
+            Err(ArchiveError::LimitExceeded(_))
+        ));
+    }
+
+    #[test]
+    fn inspect_drops_unsafe_entries_and_flags_ratio() {
+        let limits = limits();
+        let mut content = ArchiveContent::empty();
+        content.files = vec![
+            entry("/abs", 1, Some(1)),
+            entry("ok.txt", 10, Some(10)),
+            entry("bomb.txt", 1000, Some(1)),
+        ];
+        inspect(&mut content, 1000, limits.max_entries, &limits);
+
+        let names: Vec<&str> = content.files.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["ok.txt", "bomb.txt"]);
+        assert!(content.warnings.iter().any(|w| w.kind == WarningKind::UnsafePath));
+        assert!(content
+            .warnings
+            .iter()
+            .any(|w| w.kind == WarningKind::SuspiciousRatio && w.entry.as_deref() == Some("bomb.txt")));
+    }
+
+    #[test]
+    fn limited_writer_stops_at_limit() {
+        let mut out = Vec::new();
+        let mut writer = LimitedWriter::new(&mut out, 4);
+        assert!(writer.write_all(b"abcd").is_ok());
+        assert!(writer.write_all(b"e").is_err());
+        assert!(writer.exceeded);
+        assert_eq!(out, b"abcd");
+    }
+
+    #[test]
+    fn limited_reader_stops_after_limit() {
+        let mut reader = LimitedReader::new(&b"abcdef"[..], 4);
+        let mut buf = [0u8; 4];
+        assert_eq!(reader.read(&mut buf).unwrap(), 4);
+        assert!(!reader.exceeded);
+
+        let mut reader = LimitedReader::new(&b"abcdef"[..], 4);
+        assert!(io::copy(&mut reader, &mut io::sink()).is_err());
+        assert!(reader.exceeded);
+    }
+}
@@ -0,0 +1,122 @@
+use crate::FileInfo;
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+// Jejak file yang ditarik peserta (dihapus atau diganti). Blob dan baris
+// `files` sudah hilang, jadi data file disalin ke sini.
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WithdrawAction {
+    Delete,
+    Replace,
+}
+
+impl WithdrawAction {
+    fn as_str(&self) -> &'static str {
+        match self {
+            WithdrawAction::Delete => "delete",
+            WithdrawAction::Replace => "replace",
+        }
+    }
+
+    fn parse(action: &str) -> Self {
+        match action {
+            "replace" => WithdrawAction::Replace,
+            _ => WithdrawAction::Delete,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct WithdrawnFile {
+    pub meja_id: String,
+    pub file_id: String,
+    pub filename: String,
+    pub version: u32,
+    pub size: u64,
+    pub sha256: Option<String>,
+    pub uploaded_at: DateTime<Utc>,
+    pub action: WithdrawAction,
+    // File pengganti untuk action `replace`
+    pub replaced_by: Option<String>,
+    pub withdrawn_at: DateTime<Utc>,
+}
+
+pub fn init_audit_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS file_audit (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            meja_id TEXT NOT NULL,
+            file_id TEXT NOT NULL,
+            filename TEXT NOT NULL,
+            version INTEGER NOT NULL,
+            size INTEGER NOT NULL,
+            sha256 TEXT,
+            uploaded_at TEXT NOT NULL,
+            action TEXT NOT NULL,
+            replaced_by TEXT,
+            withdrawn_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+// Catat ke audit lalu hapus baris `files` dalam satu transaksi, supaya file
+// tidak pernah hilang tanpa jejak
+pub fn withdraw(
+    conn: &Connection,
+    meja_id: &str,
+    file: &FileInfo,
+    action: WithdrawAction,
+    replaced_by: Option<&str>,
+) -> rusqlite::Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "INSERT INTO file_audit (meja_id, file_id, filename, version, size, sha256, uploaded_at, action, replaced_by, withdrawn_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            meja_id,
+            file.id,
+            file.filename,
+            file.version,
+            file.size as i64,
+            file.sha256,
+            file.uploaded_at.to_rfc3339(),
+            action.as_str(),
+            replaced_by,
+            Utc::now().to_rfc3339()
+        ],
+    )?;
+    tx.execute("DELETE FROM files WHERE id = ?1", params![file.id])?;
+    tx.commit()
+}
+
+pub fn list(conn: &Connection, meja_id: Option<&str>) -> rusqlite::Result<Vec<WithdrawnFile>> {
+    let mut stmt = conn.prepare(
+        "SELECT meja_id, file_id, filename, version, size, sha256, uploaded_at, action, replaced_by, withdrawn_at
+         FROM file_audit WHERE ?1 IS NULL OR meja_id = ?1 ORDER BY id DESC",
+    )?;
+    let parse_time = |value: String| {
+        DateTime::parse_from_rfc3339(&value)
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now())
+    };
+    let rows = stmt.query_map(params![meja_id], |row| {
+        Ok(WithdrawnFile {
+            meja_id: row.get(0)?,
+            file_id: row.get(1)?,
+            filename: row.get(2)?,
+            version: row.get(3)?,
+            size: row.get(4)?,
+            sha256: row.get(5)?,
+            uploaded_at: parse_time(row.get(6)?),
+            action: WithdrawAction::parse(&row.get::<_, String>(7)?),
+            replaced_by: row.get(8)?,
+            withdrawn_at: parse_time(row.get(9)?),
+        })
+    })?;
+    rows.collect()
+}
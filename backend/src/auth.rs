@@ -0,0 +1,281 @@
+use chrono::Utc;
+use jsonwebtoken::{decode, decode_header, encode, DecodingKey, EncodingKey, Header, Validation};
+use rand::Rng;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+const TOKEN_LIFETIME_HOURS: i64 = 24;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    pub exp: usize,
+    pub iat: usize,
+    // Waktu terbit dalam milidetik untuk dibandingkan dengan cutoff sesi;
+    // 0 untuk token lama yang hanya punya `iat` per detik
+    #[serde(default)]
+    pub iat_ms: i64,
+    pub jti: String,
+    pub role: String,
+}
+
+struct SigningKey {
+    kid: String,
+    secret: String,
+}
+
+// Kunci JWT, daftar token yang dicabut, dan batas "logout semua sesi" per akun.
+// Semuanya disimpan di SQLite dan dimuat ulang saat server start.
+pub struct AuthState {
+    keys: Vec<SigningKey>,
+    revoked: HashMap<String, usize>,
+    // Milidetik; token yang terbit pada atau sebelum cutoff ditolak
+    sessions_valid_after: HashMap<String, i64>,
+}
+
+// === Database ===
+
+pub fn init_auth_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS jwt_keys (
+            kid TEXT PRIMARY KEY,
+            secret TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            retired_at TEXT
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS revoked_tokens (
+            jti TEXT PRIMARY KEY,
+            expires_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS session_cutoffs (
+            subject TEXT PRIMARY KEY,
+            valid_after INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    crate::add_column_if_missing(conn, "session_cutoffs", "valid_after_ms", "INTEGER")?;
+
+    Ok(())
+}
+
+fn generate_secret() -> String {
+    let bytes: [u8; 32] = rand::thread_rng().gen();
+    crate::digest::hex(&bytes)
+}
+
+fn insert_new_key(conn: &Connection) -> rusqlite::Result<SigningKey> {
+    let key = SigningKey {
+        kid: Uuid::new_v4().to_string(),
+        secret: generate_secret(),
+    };
+    conn.execute(
+        "INSERT INTO jwt_keys (kid, secret, created_at) VALUES (?1, ?2, ?3)",
+        params![key.kid, key.secret, Utc::now().to_rfc3339()],
+    )?;
+    Ok(key)
+}
+
+impl AuthState {
+    // Kunci aktif dibuat otomatis pada boot pertama. Kunci yang sudah
+    // dirotasi tetap dipakai verifikasi sampai token terakhirnya kedaluwarsa.
+    pub fn load(conn: &Connection) -> rusqlite::Result<AuthState> {
+        let now = Utc::now();
+        let cutoff = (now - chrono::Duration::hours(TOKEN_LIFETIME_HOURS)).to_rfc3339();
+        conn.execute(
+            "DELETE FROM jwt_keys WHERE retired_at IS NOT NULL AND retired_at < ?1",
+            params![cutoff],
+        )?;
+        conn.execute(
+            "DELETE FROM revoked_tokens WHERE expires_at < ?1",
+            params![now.timestamp()],
+        )?;
+
+        let mut keys: Vec<SigningKey> = {
+            let mut stmt = conn.prepare(
+                "SELECT kid, secret FROM jwt_keys ORDER BY retired_at IS NULL, created_at",
+            )?;
+            let rows = stmt.query_map([], |row| {
+                Ok(SigningKey {
+                    kid: row.get(0)?,
+                    secret: row.get(1)?,
+                })
+            })?;
+            rows.flatten().collect()
+        };
+        let has_active: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM jwt_keys WHERE retired_at IS NULL",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_active {
+            keys.push(insert_new_key(conn)?);
+        }
+
+        let revoked = {
+            let mut stmt = conn.prepare("SELECT jti, expires_at FROM revoked_tokens")?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as usize)))?;
+            rows.flatten().collect()
+        };
+
+        let sessions_valid_after = {
+            // Baris lama hanya menyimpan detik; seluruh detik itu dianggap sudah dicabut
+            let mut stmt = conn.prepare(
+                "SELECT subject, COALESCE(valid_after_ms, valid_after * 1000 + 999) FROM session_cutoffs",
+            )?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            rows.flatten().collect()
+        };
+
+        Ok(AuthState {
+            keys,
+            revoked,
+            sessions_valid_after,
+        })
+    }
+
+    // Kunci aktif selalu berada di posisi terakhir
+    fn active_key(&self) -> &SigningKey {
+        self.keys.last().expect("minimal satu kunci JWT")
+    }
+
+    pub fn create_token(&self, subject: &str, role: &str) -> Option<String> {
+        let now = Utc::now();
+        let expiration = now
+            .checked_add_signed(chrono::Duration::hours(TOKEN_LIFETIME_HOURS))
+            .expect("valid timestamp")
+            .timestamp() as usize;
+
+        // Token yang dibuat tepat setelah `revoke_all_sessions` (mis. ganti
+        // password) harus tetap lolos cutoff walau terbit di milidetik yang sama
+        let iat_ms = match self.sessions_valid_after.get(subject) {
+            Some(valid_after) => now.timestamp_millis().max(valid_after + 1),
+            None => now.timestamp_millis(),
+        };
+
+        let claims = Claims {
+            sub: subject.to_string(),
+            exp: expiration,
+            iat: now.timestamp() as usize,
+            iat_ms,
+            jti: Uuid::new_v4().to_string(),
+            role: role.to_string(),
+        };
+
+        let key = self.active_key();
+        let header = Header {
+            kid: Some(key.kid.clone()),
+            ..Header::default()
+        };
+        encode(&header, &claims, &EncodingKey::from_secret(key.secret.as_bytes())).ok()
+    }
+
+    pub fn decode_token(&self, token: &str) -> Option<Claims> {
+        let kid = decode_header(token).ok()?.kid?;
+        let key = self.keys.iter().find(|k| k.kid == kid)?;
+        let claims = decode::<Claims>(
+            token,
+            &DecodingKey::from_secret(key.secret.as_bytes()),
+            &Validation::default(),
+        )
+        .ok()?
+        .claims;
+
+        if self.revoked.contains_key(&claims.jti) {
+            return None;
+        }
+        if let Some(valid_after) = self.sessions_valid_after.get(&claims.sub) {
+            let issued_ms = if claims.iat_ms > 0 {
+                claims.iat_ms
+            } else {
+                claims.iat as i64 * 1000
+            };
+            if issued_ms <= *valid_after {
+                return None;
+            }
+        }
+        Some(claims)
+    }
+
+    pub fn revoke(&mut self, conn: &Connection, claims: &Claims) {
+        conn.execute(
+            "INSERT OR IGNORE INTO revoked_tokens (jti, expires_at) VALUES (?1, ?2)",
+            params![claims.jti, claims.exp as i64],
+        )
+        .ok();
+        self.revoked.insert(claims.jti.clone(), claims.exp);
+    }
+
+    // Semua token milik `subject` yang terbit sebelum saat ini menjadi tidak berlaku
+    pub fn revoke_all_sessions(&mut self, conn: &Connection, subject: &str) {
+        let now_ms = Utc::now().timestamp_millis();
+        conn.execute(
+            "INSERT INTO session_cutoffs (subject, valid_after, valid_after_ms) VALUES (?1, ?2, ?3)
+             ON CONFLICT(subject) DO UPDATE SET valid_after = excluded.valid_after, valid_after_ms = excluded.valid_after_ms",
+            params![subject, now_ms / 1000, now_ms],
+        )
+        .ok();
+        self.sessions_valid_after.insert(subject.to_string(), now_ms);
+    }
+
+    // Buat kunci aktif baru. Dengan `revoke_existing`, kunci lama langsung dihapus
+    // sehingga semua token yang sudah terbit ikut tidak berlaku.
+    pub fn rotate_key(&mut self, conn: &Connection, revoke_existing: bool) -> rusqlite::Result<()> {
+        if revoke_existing {
+            conn.execute("DELETE FROM jwt_keys", [])?;
+            self.keys.clear();
+        } else {
+            conn.execute(
+                "UPDATE jwt_keys SET retired_at = ?1 WHERE retired_at IS NULL",
+                params![Utc::now().to_rfc3339()],
+            )?;
+        }
+        let key = insert_new_key(conn)?;
+        self.keys.push(key);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn auth() -> (Connection, AuthState) {
+        let conn = Connection::open_in_memory().unwrap();
+        init_auth_tables(&conn).unwrap();
+        let auth = AuthState::load(&conn).unwrap();
+        (conn, auth)
+    }
+
+    #[test]
+    fn revoke_all_sessions_rejects_tokens_from_the_same_second() {
+        let (conn, mut auth) = auth();
+        let old = auth.create_token("admin", "superadmin").unwrap();
+        auth.revoke_all_sessions(&conn, "admin");
+        let new = auth.create_token("admin", "superadmin").unwrap();
+
+        assert!(auth.decode_token(&old).is_none());
+        assert!(auth.decode_token(&new).is_some());
+    }
+
+    #[test]
+    fn cutoffs_survive_reload() {
+        let (conn, mut auth) = auth();
+        let old = auth.create_token("admin", "superadmin").unwrap();
+        auth.revoke_all_sessions(&conn, "admin");
+        let new = auth.create_token("admin", "superadmin").unwrap();
+
+        let reloaded = AuthState::load(&conn).unwrap();
+        assert!(reloaded.decode_token(&old).is_none());
+        assert!(reloaded.decode_token(&new).is_some());
+    }
+}
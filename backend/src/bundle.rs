@@ -0,0 +1,112 @@
+use async_zip::tokio::write::ZipFileWriter;
+use async_zip::{Compression, ZipDateTime, ZipEntryBuilder};
+use chrono::{DateTime, Utc};
+use std::io;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio_util::compat::FuturesAsyncWriteCompatExt;
+
+use crate::{digest, versions, Meja};
+
+pub struct BundleItem {
+    pub nomor: u32,
+    // Path di dalam ZIP, mis. `Meja_3_Budi/main.py`
+    pub zip_path: String,
+    pub path: String,
+    pub uploaded_at: DateTime<Utc>,
+    pub seconds_late: Option<i64>,
+}
+
+pub fn meja_folder(meja: &Meja) -> String {
+    let folder = match meja.nama_peserta.as_deref().map(str::trim) {
+        Some(nama) if !nama.is_empty() => format!("Meja_{}_{}", meja.nomor, nama),
+        _ => format!("Meja_{}", meja.nomor),
+    };
+    sanitize_filename::sanitize(folder)
+}
+
+// Versi yang dinilai (final, atau terbaru) memakai nama aslinya, versi lain
+// masuk ke `versi_{n}/` kecuali `latest_only`
+pub fn meja_items(meja: &Meja, latest_only: bool) -> Vec<BundleItem> {
+    let folder = meja_folder(meja);
+    let mut items = vec![];
+    for (filename, file_versions) in versions::by_filename(&meja.files) {
+        let submitted = versions::submitted(&file_versions).map(|f| f.id.as_str());
+        for file in file_versions {
+            let zip_path = if Some(file.id.as_str()) == submitted {
+                format!("{}/{}", folder, filename)
+            } else if latest_only {
+                continue;
+            } else {
+                format!("{}/versi_{}/{}", folder, file.version, filename)
+            };
+            items.push(BundleItem {
+                nomor: meja.nomor,
+                zip_path,
+                path: file.path.clone(),
+                uploaded_at: file.uploaded_at,
+                seconds_late: file.seconds_late,
+            });
+        }
+    }
+    items.sort_by(|a, b| a.zip_path.cmp(&b.zip_path));
+    items
+}
+
+fn csv_field(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+// Kosong untuk upload yang tepat waktu
+fn late_field(seconds_late: Option<i64>) -> String {
+    seconds_late.map(|s| s.to_string()).unwrap_or_default()
+}
+
+// ZIP ditulis sambil dibaca dari disk (tanpa kompresi, memakai data descriptor)
+// sehingga memori yang dipakai tetap kecil berapa pun total ukuran submission.
+// manifest.csv ditulis terakhir karena SHA-256 dihitung saat file disalin.
+pub async fn write_bundle<W: AsyncWrite + Unpin>(writer: W, items: Vec<BundleItem>) -> io::Result<()> {
+    let mut zip = ZipFileWriter::with_tokio(writer);
+    let mut manifest = String::from("meja,file,size,uploaded_at,sha256,seconds_late\n");
+    for item in items {
+        // File yang hilang dari disk tetap dicatat di manifest tanpa hash
+        let Ok(mut file) = tokio::fs::File::open(&item.path).await else {
+            manifest.push_str(&format!(
+                "{},{},,{},,{}\n",
+                item.nomor,
+                csv_field(&item.zip_path),
+                item.uploaded_at.to_rfc3339(),
+                late_field(item.seconds_late)
+            ));
+            continue;
+        };
+
+        let entry = ZipEntryBuilder::new(item.zip_path.clone().into(), Compression::Stored)
+            .last_modification_date(ZipDateTime::from_chrono(&item.uploaded_at));
+        let mut entry_writer = zip
+            .write_entry_stream(entry)
+            .await
+            .map_err(io::Error::other)?
+            .compat_write();
+
+        let (hash, size) = digest::copy_hashed(&mut file, &mut entry_writer).await?;
+        entry_writer.into_inner().close().await.map_err(io::Error::other)?;
+
+        manifest.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            item.nomor,
+            csv_field(&item.zip_path),
+            size,
+            item.uploaded_at.to_rfc3339(),
+            hash,
+            late_field(item.seconds_late)
+        ));
+    }
+
+    let entry = ZipEntryBuilder::new("manifest.csv".to_string().into(), Compression::Stored)
+        .last_modification_date(ZipDateTime::from_chrono(&Utc::now()));
+    zip.write_entry_whole(entry, manifest.as_bytes())
+        .await
+        .map_err(io::Error::other)?;
+    let mut writer = zip.close().await.map_err(io::Error::other)?.into_inner();
+    writer.shutdown().await
+}
@@ -0,0 +1,223 @@
+use clap::Parser;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+const DEFAULT_CONFIG_FILE: &str = "lomba.toml";
+
+static SETTINGS: OnceLock<Settings> = OnceLock::new();
+
+// === Settings ===
+
+#[derive(Clone, Debug)]
+pub struct Settings {
+    pub host: String,
+    pub port: u16,
+    pub storage_dir: PathBuf,
+    pub max_file_size: u64,
+    pub timer_broadcast_interval_ms: u64,
+    pub workers: usize,
+    pub multipart_memory_limit: usize,
+    pub archive_max_total_size: u64,
+    pub archive_max_entries: usize,
+    pub archive_max_ratio: u64,
+    pub archive_max_path_depth: usize,
+    pub archive_max_nesting: usize,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            host: "0.0.0.0".to_string(),
+            port: 3001,
+            storage_dir: PathBuf::from("./storage"),
+            max_file_size: 300 * 1024 * 1024, // 300MB
+            timer_broadcast_interval_ms: 250, // Broadcast setiap 250ms untuk realtime
+            workers: 4,
+            multipart_memory_limit: 50 * 1024 * 1024, // 50MB memory buffer
+            archive_max_total_size: 2 * 1024 * 1024 * 1024, // 2GB isi archive setelah diekstrak
+            archive_max_entries: 10_000,
+            archive_max_ratio: 100,
+            archive_max_path_depth: 32,
+            archive_max_nesting: 3, // archive di dalam archive; 0 = tidak dibuka
+        }
+    }
+}
+
+impl Settings {
+    pub fn bind_address(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+
+    pub fn max_file_size_mb(&self) -> u64 {
+        self.max_file_size / (1024 * 1024)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.host.trim().is_empty() {
+            return Err("host tidak boleh kosong".to_string());
+        }
+        if self.port == 0 {
+            return Err("port harus lebih dari 0".to_string());
+        }
+        if self.storage_dir.as_os_str().is_empty() {
+            return Err("storage_dir tidak boleh kosong".to_string());
+        }
+        if self.max_file_size == 0 {
+            return Err("max_file_size_mb harus lebih dari 0".to_string());
+        }
+        if self.timer_broadcast_interval_ms < 50 {
+            return Err("timer_broadcast_interval_ms minimal 50".to_string());
+        }
+        if self.workers == 0 {
+            return Err("workers harus lebih dari 0".to_string());
+        }
+        if self.multipart_memory_limit == 0 || self.multipart_memory_limit as u64 > self.max_file_size {
+            return Err("multipart_memory_limit_mb harus antara 1 dan max_file_size_mb".to_string());
+        }
+        if self.archive_max_total_size == 0 {
+            return Err("archive_max_total_size_mb harus lebih dari 0".to_string());
+        }
+        if self.archive_max_entries == 0 {
+            return Err("archive_max_entries harus lebih dari 0".to_string());
+        }
+        if self.archive_max_ratio == 0 {
+            return Err("archive_max_ratio harus lebih dari 0".to_string());
+        }
+        if self.archive_max_path_depth == 0 {
+            return Err("archive_max_path_depth harus lebih dari 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+// === Sources ===
+
+// Urutan prioritas: flag CLI > environment variable > file TOML > default
+#[derive(Parser)]
+#[command(name = "lomba_server", about = "Server lomba coding")]
+struct Cli {
+    /// Path file konfigurasi TOML (default: ./lomba.toml jika ada)
+    #[arg(long, env = "LOMBA_CONFIG")]
+    config: Option<PathBuf>,
+    #[arg(long, env = "LOMBA_HOST")]
+    host: Option<String>,
+    #[arg(long, env = "LOMBA_PORT")]
+    port: Option<u16>,
+    #[arg(long, env = "LOMBA_STORAGE_DIR")]
+    storage_dir: Option<PathBuf>,
+    #[arg(long, env = "LOMBA_MAX_FILE_SIZE_MB")]
+    max_file_size_mb: Option<u64>,
+    #[arg(long, env = "LOMBA_TIMER_BROADCAST_INTERVAL_MS")]
+    timer_broadcast_interval_ms: Option<u64>,
+    #[arg(long, env = "LOMBA_WORKERS")]
+    workers: Option<usize>,
+    #[arg(long, env = "LOMBA_MULTIPART_MEMORY_LIMIT_MB")]
+    multipart_memory_limit_mb: Option<usize>,
+    #[arg(long, env = "LOMBA_ARCHIVE_MAX_TOTAL_SIZE_MB")]
+    archive_max_total_size_mb: Option<u64>,
+    #[arg(long, env = "LOMBA_ARCHIVE_MAX_ENTRIES")]
+    archive_max_entries: Option<usize>,
+    #[arg(long, env = "LOMBA_ARCHIVE_MAX_RATIO")]
+    archive_max_ratio: Option<u64>,
+    #[arg(long, env = "LOMBA_ARCHIVE_MAX_PATH_DEPTH")]
+    archive_max_path_depth: Option<usize>,
+    #[arg(long, env = "LOMBA_ARCHIVE_MAX_NESTING")]
+    archive_max_nesting: Option<usize>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileSettings {
+    host: Option<String>,
+    port: Option<u16>,
+    storage_dir: Option<PathBuf>,
+    max_file_size_mb: Option<u64>,
+    timer_broadcast_interval_ms: Option<u64>,
+    workers: Option<usize>,
+    multipart_memory_limit_mb: Option<usize>,
+    archive_max_total_size_mb: Option<u64>,
+    archive_max_entries: Option<usize>,
+    archive_max_ratio: Option<u64>,
+    archive_max_path_depth: Option<usize>,
+    archive_max_nesting: Option<usize>,
+}
+
+fn read_config_file(path: &Path, required: bool) -> Result<FileSettings, String> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => toml::from_str(&content)
+            .map_err(|e| format!("Gagal membaca {}: {}", path.display(), e)),
+        Err(_) if !required => Ok(FileSettings::default()),
+        Err(e) => Err(format!("Gagal membuka {}: {}", path.display(), e)),
+    }
+}
+
+fn too_large(key: &str) -> String {
+    format!("{} terlalu besar", key)
+}
+
+impl Settings {
+    pub fn load() -> Result<Settings, String> {
+        let cli = Cli::parse();
+
+        let file = match &cli.config {
+            Some(path) => read_config_file(path, true)?,
+            None => read_config_file(Path::new(DEFAULT_CONFIG_FILE), false)?,
+        };
+
+        let mut settings = Settings::default();
+        if let Some(host) = cli.host.or(file.host) {
+            settings.host = host;
+        }
+        if let Some(port) = cli.port.or(file.port) {
+            settings.port = port;
+        }
+        if let Some(storage_dir) = cli.storage_dir.or(file.storage_dir) {
+            settings.storage_dir = storage_dir;
+        }
+        if let Some(mb) = cli.max_file_size_mb.or(file.max_file_size_mb) {
+            settings.max_file_size = mb
+                .checked_mul(1024 * 1024)
+                .ok_or_else(|| too_large("max_file_size_mb"))?;
+        }
+        if let Some(ms) = cli.timer_broadcast_interval_ms.or(file.timer_broadcast_interval_ms) {
+            settings.timer_broadcast_interval_ms = ms;
+        }
+        if let Some(workers) = cli.workers.or(file.workers) {
+            settings.workers = workers;
+        }
+        if let Some(mb) = cli.multipart_memory_limit_mb.or(file.multipart_memory_limit_mb) {
+            settings.multipart_memory_limit = mb
+                .checked_mul(1024 * 1024)
+                .ok_or_else(|| too_large("multipart_memory_limit_mb"))?;
+        }
+        if let Some(mb) = cli.archive_max_total_size_mb.or(file.archive_max_total_size_mb) {
+            settings.archive_max_total_size = mb
+                .checked_mul(1024 * 1024)
+                .ok_or_else(|| too_large("archive_max_total_size_mb"))?;
+        }
+        if let Some(entries) = cli.archive_max_entries.or(file.archive_max_entries) {
+            settings.archive_max_entries = entries;
+        }
+        if let Some(ratio) = cli.archive_max_ratio.or(file.archive_max_ratio) {
+            settings.archive_max_ratio = ratio;
+        }
+        if let Some(depth) = cli.archive_max_path_depth.or(file.archive_max_path_depth) {
+            settings.archive_max_path_depth = depth;
+        }
+        if let Some(nesting) = cli.archive_max_nesting.or(file.archive_max_nesting) {
+            settings.archive_max_nesting = nesting;
+        }
+
+        settings.validate()?;
+        Ok(settings)
+    }
+}
+
+pub fn init(settings: Settings) {
+    SETTINGS.set(settings).expect("settings sudah diinisialisasi");
+}
+
+pub fn settings() -> &'static Settings {
+    SETTINGS.get().expect("settings belum diinisialisasi")
+}
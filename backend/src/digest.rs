@@ -0,0 +1,28 @@
+use sha2::{Digest, Sha256};
+use std::io;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// Salin `reader` ke `writer` sambil menghitung SHA-256; mengembalikan hash hex dan jumlah byte
+pub async fn copy_hashed<R, W>(reader: &mut R, writer: &mut W) -> io::Result<(String, u64)>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 256 * 1024];
+    let mut size: u64 = 0;
+    loop {
+        let n = reader.read(&mut buffer).await?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+        writer.write_all(&buffer[..n]).await?;
+        size += n as u64;
+    }
+    Ok((hex(&hasher.finalize()), size))
+}
@@ -0,0 +1,93 @@
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// Tambahan waktu per meja (misal listrik meja mati 10 menit). Deadline meja
+// adalah akhir timer global ditambah total tambahan waktunya.
+
+// Batas satu kali pemberian tambahan waktu
+pub const MAX_EXTENSION_SECONDS: i64 = 24 * 60 * 60;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TimeExtension {
+    pub id: i64,
+    pub seconds: i64,
+    pub reason: String,
+    pub granted_by: String,
+    pub granted_at: DateTime<Utc>,
+}
+
+pub fn init_extension_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS time_extensions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            meja_id TEXT NOT NULL,
+            seconds INTEGER NOT NULL,
+            reason TEXT NOT NULL,
+            granted_by TEXT NOT NULL,
+            granted_at TEXT NOT NULL,
+            FOREIGN KEY (meja_id) REFERENCES meja(id)
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+pub fn grant(
+    conn: &Connection,
+    meja_id: &str,
+    seconds: i64,
+    reason: &str,
+    granted_by: &str,
+) -> rusqlite::Result<TimeExtension> {
+    let granted_at = Utc::now();
+    conn.execute(
+        "INSERT INTO time_extensions (meja_id, seconds, reason, granted_by, granted_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![meja_id, seconds, reason, granted_by, granted_at.to_rfc3339()],
+    )?;
+    Ok(TimeExtension {
+        id: conn.last_insert_rowid(),
+        seconds,
+        reason: reason.to_string(),
+        granted_by: granted_by.to_string(),
+        granted_at,
+    })
+}
+
+// true jika ada yang dihapus
+pub fn revoke(conn: &Connection, meja_id: &str, id: i64) -> rusqlite::Result<bool> {
+    let deleted = conn.execute(
+        "DELETE FROM time_extensions WHERE id = ?1 AND meja_id = ?2",
+        params![id, meja_id],
+    )?;
+    Ok(deleted > 0)
+}
+
+// Semua tambahan waktu, dikelompokkan per meja
+pub fn load_all(conn: &Connection) -> rusqlite::Result<HashMap<String, Vec<TimeExtension>>> {
+    let mut stmt = conn.prepare(
+        "SELECT meja_id, id, seconds, reason, granted_by, granted_at FROM time_extensions ORDER BY id",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        let granted_at: String = row.get(5)?;
+        Ok((
+            row.get::<_, String>(0)?,
+            TimeExtension {
+                id: row.get(1)?,
+                seconds: row.get(2)?,
+                reason: row.get(3)?,
+                granted_by: row.get(4)?,
+                granted_at: DateTime::parse_from_rfc3339(&granted_at)
+                    .map(|dt| dt.with_timezone(&Utc))
+                    .unwrap_or_else(|_| Utc::now()),
+            },
+        ))
+    })?;
+
+    let mut by_meja: HashMap<String, Vec<TimeExtension>> = HashMap::new();
+    for (meja_id, extension) in rows.flatten() {
+        by_meja.entry(meja_id).or_default().push(extension);
+    }
+    Ok(by_meja)
+}
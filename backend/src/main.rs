@@ -1,25 +1,48 @@
 use actix_cors::Cors;
-use actix_files::Files;
+use actix_files::NamedFile;
 use actix_multipart::Multipart;
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
 use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer, Responder};
 use actix_ws::Message;
 use bcrypt::{hash, verify, DEFAULT_COST};
+use bytestring::ByteString;
 use chrono::{DateTime, Utc};
 use futures_util::{StreamExt, TryStreamExt};
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
 use rand::Rng;
 use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::{broadcast, Mutex, RwLock};
 use tokio::io::AsyncWriteExt;
 use uuid::Uuid;
 
-const JWT_SECRET: &[u8] = b"lomba-coding-secret-key-2024";
-const MAX_FILE_SIZE: u64 = 300 * 1024 * 1024; // 300MB
-const TIMER_BROADCAST_INTERVAL_MS: u64 = 250; // Broadcast setiap 250ms untuk realtime
+mod archive;
+mod audit;
+mod auth;
+mod bundle;
+mod config;
+mod digest;
+mod extensions;
+mod ratelimit;
+mod receipt;
+mod resumable;
+mod rules;
+mod timer;
+mod versions;
+
+use archive::ArchiveError;
+use audit::WithdrawAction;
+use auth::{AuthState, Claims};
+use config::{settings, Settings};
+use extensions::TimeExtension;
+use ratelimit::{LimitKey, LimitScope, LoginLimiter};
+use receipt::Receipt;
+use resumable::UploadLocks;
+use rules::{MejaUsage, RuleViolation, UploadRules};
+use timer::{save_timer_to_db, TimerState};
 
 // === Data Structures ===
 
@@ -27,10 +50,52 @@ const TIMER_BROADCAST_INTERVAL_MS: u64 = 250; // Broadcast setiap 250ms untuk re
 pub struct Meja {
     pub id: String,
     pub nomor: u32,
+    // Kosong di view untuk admin yang tidak boleh mengelola meja
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub kode: String,
     pub nama_peserta: Option<String>,
     pub files: Vec<FileInfo>,
     pub last_upload: Option<DateTime<Utc>>,
+    // Diisi saat peserta final submit; selama terisi upload ditolak
+    pub submitted_at: Option<DateTime<Utc>>,
+    // Tambahan waktu dari admin, menggeser deadline meja ini saja
+    pub extensions: Vec<TimeExtension>,
+}
+
+impl Meja {
+    pub fn status(&self) -> &'static str {
+        if self.submitted_at.is_some() {
+            "Selesai"
+        } else if self.files.is_empty() {
+            "Belum Upload"
+        } else {
+            "Sudah Upload"
+        }
+    }
+
+    pub fn without_kode(&self) -> Meja {
+        Meja {
+            kode: String::new(),
+            ..self.clone()
+        }
+    }
+
+    pub fn extension_seconds(&self) -> i64 {
+        // Tidak pernah terjadi dengan batas per tambahan, tapi data lama tidak boleh membuat panic
+        self.extensions
+            .iter()
+            .try_fold(0i64, |total, e| total.checked_add(e.seconds))
+            .unwrap_or(i64::MAX)
+    }
+
+    // Keterlambatan terbesar di antara versi yang dinilai; None jika semuanya tepat waktu
+    pub fn seconds_late(&self) -> Option<i64> {
+        versions::by_filename(&self.files)
+            .values()
+            .filter_map(|file_versions| versions::submitted(file_versions))
+            .filter_map(|file| file.seconds_late)
+            .max()
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -40,14 +105,14 @@ pub struct FileInfo {
     pub size: u64,
     pub uploaded_at: DateTime<Utc>,
     pub path: String,
-}
-
-#[derive(Clone, Serialize, Deserialize)]
-pub struct TimerState {
-    pub is_running: bool,
-    pub duration_seconds: i64,
-    pub remaining_seconds: i64,
-    pub started_at: Option<DateTime<Utc>>,
+    // Versi ke-n untuk nama file yang sama di meja ini, mulai dari 1
+    pub version: u32,
+    pub is_final: bool,
+    // Hex SHA-256 isi file; None untuk upload dari versi server lama
+    pub sha256: Option<String>,
+    // Diterima setelah timer habis (dalam masa tenggang)
+    pub late: bool,
+    pub seconds_late: Option<i64>,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -64,35 +129,213 @@ pub struct AppState {
     pub timer: TimerState,
     pub soal_files: Vec<SoalFile>,
     pub lomba_title: String,
+    pub upload_rules: UploadRules,
 }
 
 pub struct SharedState {
     pub state: RwLock<AppState>,
-    pub broadcast_tx: broadcast::Sender<String>,
+    pub broadcast_tx: broadcast::Sender<Arc<StateFrame>>,
     pub db: Mutex<Connection>,
+    pub auth: std::sync::RwLock<AuthState>,
+    pub login_limiter: std::sync::Mutex<LoginLimiter>,
+    pub upload_locks: UploadLocks,
+    pub receipt_key: String,
 }
 
-// === Auth Structures ===
+// === State Views ===
+
+// Siapa yang melihat state, ditentukan dari token pada request/WebSocket
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum ViewRole {
+    Public,
+    Peserta(String),
+    Admin(AdminRole),
+}
+
+// Progress meja tanpa id, kode, maupun nama peserta
+#[derive(Serialize)]
+pub struct MejaProgress {
+    pub nomor: u32,
+    pub jumlah_file: usize,
+    pub last_upload: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize)]
+pub struct StateView<'a> {
+    pub meja_list: HashMap<&'a str, Cow<'a, Meja>>,
+    pub progress: Vec<MejaProgress>,
+    // Untuk peserta sudah digeser sesuai tambahan waktu mejanya
+    pub timer: Cow<'a, TimerState>,
+    pub soal_files: &'a [SoalFile],
+    pub lomba_title: &'a str,
+    pub upload_rules: &'a UploadRules,
+}
+
+impl<'a> StateView<'a> {
+    pub fn new(state: &'a AppState, role: &ViewRole) -> Self {
+        let meja_list = match role {
+            // Kode meja cukup untuk login sebagai peserta, jadi hanya role
+            // yang mengelola meja yang melihatnya
+            ViewRole::Admin(admin_role) if admin_role.allows(Permission::ManageLomba) => state
+                .meja_list
+                .iter()
+                .map(|(id, meja)| (id.as_str(), Cow::Borrowed(meja)))
+                .collect(),
+            ViewRole::Admin(_) => state
+                .meja_list
+                .iter()
+                .map(|(id, meja)| (id.as_str(), Cow::Owned(meja.without_kode())))
+                .collect(),
+            ViewRole::Peserta(meja_id) => state
+                .meja_list
+                .get_key_value(meja_id)
+                .map(|(id, meja)| (id.as_str(), Cow::Borrowed(meja)))
+                .into_iter()
+                .collect(),
+            ViewRole::Public => HashMap::new(),
+        };
+
+        let timer = match role {
+            ViewRole::Peserta(meja_id) => match state.meja_list.get(meja_id) {
+                Some(meja) => Cow::Owned(state.timer.for_meja(meja.extension_seconds(), Utc::now())),
+                None => Cow::Borrowed(&state.timer),
+            },
+            _ => Cow::Borrowed(&state.timer),
+        };
+
+        let mut progress: Vec<MejaProgress> = state
+            .meja_list
+            .values()
+            .map(|meja| MejaProgress {
+                nomor: meja.nomor,
+                jumlah_file: meja.files.len(),
+                last_upload: meja.last_upload,
+            })
+            .collect();
+        progress.sort_by_key(|p| p.nomor);
+
+        StateView {
+            meja_list,
+            progress,
+            timer,
+            soal_files: &state.soal_files,
+            lomba_title: &state.lomba_title,
+            upload_rules: &state.upload_rules,
+        }
+    }
+}
+
+// Satu frame per perubahan state. Tiap view diserialisasi sekali oleh koneksi
+// pertama yang membutuhkannya, lalu dipakai bersama koneksi lain dengan role sama.
+#[derive(Default)]
+pub struct StateFrame {
+    views: std::sync::Mutex<HashMap<ViewRole, ByteString>>,
+}
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Claims {
-    pub sub: String,
-    pub exp: usize,
-    pub role: String,
+impl StateFrame {
+    async fn view(&self, shared: &SharedState, role: &ViewRole) -> Option<ByteString> {
+        let cached = self.views.lock().ok()?.get(role).cloned();
+        if cached.is_some() {
+            return cached;
+        }
+        let json = {
+            let state = shared.state.read().await;
+            ByteString::from(serde_json::to_string(&StateView::new(&state, role)).ok()?)
+        };
+        let mut views = self.views.lock().ok()?;
+        Some(views.entry(role.clone()).or_insert(json).clone())
+    }
 }
 
+// === Auth Structures ===
+
 #[derive(Deserialize)]
 pub struct LoginAdminRequest {
     pub username: String,
     pub password: String,
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AdminRole {
+    Superadmin,
+    Operator,
+    Juri,
+}
+
+#[derive(Clone, Copy)]
+pub enum Permission {
+    ManageAccounts,
+    ManageLomba,
+    ControlTimer,
+    ManageLockouts,
+    ReadSubmissions,
+}
+
+impl AdminRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AdminRole::Superadmin => "superadmin",
+            AdminRole::Operator => "operator",
+            AdminRole::Juri => "juri",
+        }
+    }
+
+    pub fn parse(role: &str) -> Option<Self> {
+        match role {
+            "superadmin" => Some(AdminRole::Superadmin),
+            "operator" => Some(AdminRole::Operator),
+            "juri" => Some(AdminRole::Juri),
+            _ => None,
+        }
+    }
+
+    // Superadmin boleh semuanya, operator mengatur timer dan lockout login,
+    // juri hanya membaca submission
+    pub fn allows(&self, permission: Permission) -> bool {
+        match self {
+            AdminRole::Superadmin => true,
+            AdminRole::Operator => matches!(
+                permission,
+                Permission::ControlTimer | Permission::ManageLockouts | Permission::ReadSubmissions
+            ),
+            AdminRole::Juri => matches!(permission, Permission::ReadSubmissions),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct AdminUser {
+    pub id: i64,
+    pub username: String,
+    pub role: AdminRole,
+}
+
+#[derive(Deserialize)]
+pub struct CreateAdminRequest {
+    pub username: String,
+    pub password: String,
+    pub role: AdminRole,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateAdminRequest {
+    pub password: Option<String>,
+    pub role: Option<AdminRole>,
+}
+
 #[derive(Deserialize)]
 pub struct ChangePasswordRequest {
     pub old_password: String,
     pub new_password: String,
 }
 
+#[derive(Deserialize)]
+pub struct RotateKeyRequest {
+    #[serde(default)]
+    pub revoke_existing: bool,
+}
+
 #[derive(Serialize)]
 pub struct AuthResponse {
     pub success: bool,
@@ -117,26 +360,47 @@ pub struct SetTimerRequest {
     pub duration_minutes: i64,
 }
 
+#[derive(Deserialize)]
+pub struct SetGraceRequest {
+    pub grace_minutes: i64,
+}
+
+#[derive(Deserialize)]
+pub struct GrantExtensionRequest {
+    pub seconds: i64,
+    pub reason: String,
+}
+
 #[derive(Deserialize)]
 pub struct AdjustTimerRequest {
     pub seconds: i64,
 }
 
+#[derive(Deserialize)]
+pub struct InitUploadRequest {
+    pub filename: String,
+    pub size: u64,
+}
+
 #[derive(Deserialize)]
 pub struct UpdatePesertaRequest {
     pub nama: String,
 }
 
-#[derive(Serialize)]
-pub struct ArchiveContent {
-    pub files: Vec<ArchiveEntry>,
+#[derive(Deserialize)]
+pub struct BundleQuery {
+    pub meja: Option<u32>,
+    pub from: Option<u32>,
+    pub to: Option<u32>,
+    #[serde(default)]
+    pub latest: bool,
 }
 
-#[derive(Serialize)]
-pub struct ArchiveEntry {
+#[derive(Deserialize)]
+pub struct ArchiveEntryQuery {
     pub name: String,
-    pub size: u64,
-    pub is_dir: bool,
+    #[serde(default)]
+    pub download: bool,
 }
 
 #[derive(Serialize)]
@@ -158,6 +422,7 @@ fn init_database(conn: &Connection) -> rusqlite::Result<()> {
         )",
         [],
     )?;
+    add_column_if_missing(conn, "admin", "role", "TEXT NOT NULL DEFAULT 'superadmin'")?;
 
     conn.execute(
         "CREATE TABLE IF NOT EXISTS meja (
@@ -168,6 +433,7 @@ fn init_database(conn: &Connection) -> rusqlite::Result<()> {
         )",
         [],
     )?;
+    add_column_if_missing(conn, "meja", "submitted_at", "TEXT")?;
 
     conn.execute(
         "CREATE TABLE IF NOT EXISTS files (
@@ -181,6 +447,12 @@ fn init_database(conn: &Connection) -> rusqlite::Result<()> {
         )",
         [],
     )?;
+    if add_column_if_missing(conn, "files", "version", "INTEGER NOT NULL DEFAULT 1")? {
+        versions::renumber_versions(conn)?;
+    }
+    add_column_if_missing(conn, "files", "is_final", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "files", "sha256", "TEXT")?;
+    add_column_if_missing(conn, "files", "seconds_late", "INTEGER")?;
 
     conn.execute(
         "CREATE TABLE IF NOT EXISTS soal (
@@ -192,35 +464,35 @@ fn init_database(conn: &Connection) -> rusqlite::Result<()> {
         [],
     )?;
 
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS timer (
-            id INTEGER PRIMARY KEY CHECK (id = 1),
-            is_running INTEGER NOT NULL DEFAULT 0,
-            duration_seconds INTEGER NOT NULL DEFAULT 3600,
-            remaining_seconds INTEGER NOT NULL DEFAULT 3600,
-            started_at TEXT
-        )",
-        [],
-    )?;
+    timer::init_timer_tables(conn)?;
 
     let default_hash = hash("admin123", DEFAULT_COST).unwrap();
     conn.execute(
-        "INSERT OR IGNORE INTO admin (id, username, password_hash) VALUES (1, 'admin', ?1)",
+        "INSERT OR IGNORE INTO admin (id, username, password_hash, role) VALUES (1, 'admin', ?1, 'superadmin')",
         params![default_hash],
     )?;
 
-    conn.execute(
-        "INSERT OR IGNORE INTO timer (id, is_running, duration_seconds, remaining_seconds) VALUES (1, 0, 3600, 3600)",
-        [],
-    )?;
-
     Ok(())
 }
 
+// Migrasi sederhana untuk database lama yang belum punya kolom baru.
+// Mengembalikan true jika kolom baru saja ditambahkan.
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> rusqlite::Result<bool> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .flatten()
+        .any(|name| name == column);
+    if !exists {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+    }
+    Ok(!exists)
+}
+
 fn load_state_from_db(conn: &Connection) -> AppState {
     let mut meja_list: HashMap<String, Meja> = HashMap::new();
 
-    if let Ok(mut stmt) = conn.prepare("SELECT id, nomor, kode, nama_peserta FROM meja") {
+    if let Ok(mut stmt) = conn.prepare("SELECT id, nomor, kode, nama_peserta, submitted_at FROM meja") {
         if let Ok(rows) = stmt.query_map([], |row| {
             Ok(Meja {
                 id: row.get(0)?,
@@ -229,6 +501,11 @@ fn load_state_from_db(conn: &Connection) -> AppState {
                 nama_peserta: row.get(3)?,
                 files: vec![],
                 last_upload: None,
+                submitted_at: row
+                    .get::<_, Option<String>>(4)?
+                    .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+                    .map(|dt| dt.with_timezone(&Utc)),
+                extensions: vec![],
             })
         }) {
             for meja in rows.flatten() {
@@ -237,12 +514,21 @@ fn load_state_from_db(conn: &Connection) -> AppState {
         }
     }
 
-    if let Ok(mut stmt) = conn.prepare("SELECT id, meja_id, filename, size, uploaded_at, path FROM files ORDER BY uploaded_at DESC") {
+    if let Ok(by_meja) = extensions::load_all(conn) {
+        for (meja_id, meja_extensions) in by_meja {
+            if let Some(meja) = meja_list.get_mut(&meja_id) {
+                meja.extensions = meja_extensions;
+            }
+        }
+    }
+
+    if let Ok(mut stmt) = conn.prepare("SELECT id, meja_id, filename, size, uploaded_at, path, version, is_final, sha256, seconds_late FROM files ORDER BY uploaded_at, version") {
         if let Ok(rows) = stmt.query_map([], |row| {
             let uploaded_at_str: String = row.get(4)?;
             let uploaded_at = DateTime::parse_from_rfc3339(&uploaded_at_str)
                 .map(|dt| dt.with_timezone(&Utc))
                 .unwrap_or_else(|_| Utc::now());
+            let seconds_late: Option<i64> = row.get(9)?;
             Ok((
                 row.get::<_, String>(1)?,
                 FileInfo {
@@ -251,6 +537,11 @@ fn load_state_from_db(conn: &Connection) -> AppState {
                     size: row.get(3)?,
                     uploaded_at,
                     path: row.get(5)?,
+                    version: row.get(6)?,
+                    is_final: row.get::<_, i32>(7)? != 0,
+                    sha256: row.get(8)?,
+                    late: seconds_late.is_some(),
+                    seconds_late,
                 },
             ))
         }) {
@@ -284,51 +575,17 @@ fn load_state_from_db(conn: &Connection) -> AppState {
         }
     }
 
-    let timer = if let Ok(mut stmt) = conn.prepare("SELECT is_running, duration_seconds, remaining_seconds, started_at FROM timer WHERE id = 1") {
-        stmt.query_row([], |row| {
-            let started_at_str: Option<String> = row.get(3)?;
-            let started_at = started_at_str.and_then(|s| {
-                DateTime::parse_from_rfc3339(&s)
-                    .map(|dt| dt.with_timezone(&Utc))
-                    .ok()
-            });
-            Ok(TimerState {
-                is_running: row.get::<_, i32>(0)? != 0,
-                duration_seconds: row.get(1)?,
-                remaining_seconds: row.get(2)?,
-                started_at,
-            })
-        }).unwrap_or(TimerState {
-            is_running: false,
-            duration_seconds: 3600,
-            remaining_seconds: 3600,
-            started_at: None,
-        })
-    } else {
-        TimerState {
-            is_running: false,
-            duration_seconds: 3600,
-            remaining_seconds: 3600,
-            started_at: None,
-        }
-    };
+    let timer = timer::load_timer(conn, Utc::now());
 
     AppState {
         meja_list,
         timer,
         soal_files,
         lomba_title: "Lomba Coding".to_string(),
+        upload_rules: rules::load_rules(conn),
     }
 }
 
-fn save_timer_to_db(conn: &Connection, timer: &TimerState) {
-    let started_at = timer.started_at.map(|dt| dt.to_rfc3339());
-    conn.execute(
-        "UPDATE timer SET is_running = ?1, duration_seconds = ?2, remaining_seconds = ?3, started_at = ?4 WHERE id = 1",
-        params![timer.is_running as i32, timer.duration_seconds, timer.remaining_seconds, started_at],
-    ).ok();
-}
-
 // === Helper Functions ===
 
 fn generate_kode() -> String {
@@ -338,7 +595,7 @@ fn generate_kode() -> String {
 }
 
 fn get_storage_path() -> PathBuf {
-    let path = PathBuf::from("./storage");
+    let path = settings().storage_dir.clone();
     std::fs::create_dir_all(&path).ok();
     path
 }
@@ -349,101 +606,222 @@ fn get_uploads_path(meja_id: &str) -> PathBuf {
     path
 }
 
+// Setiap versi punya folder sendiri dengan nama file asli, supaya ekstensi
+// dan nama volume archive (`x.part2.rar`) tetap seperti yang diupload
+fn upload_blob_path(meja_id: &str, file_id: &str, filename: &str) -> PathBuf {
+    let dir = get_uploads_path(meja_id).join(file_id);
+    std::fs::create_dir_all(&dir).ok();
+    dir.join(filename)
+}
+
+// Hapus blob beserta folder versinya jika sudah kosong
+async fn remove_blob(path: &Path) {
+    tokio::fs::remove_file(path).await.ok();
+    if let Some(dir) = path.parent() {
+        tokio::fs::remove_dir(dir).await.ok();
+    }
+}
+
 fn get_soal_path() -> PathBuf {
     let path = get_storage_path().join("soal");
     std::fs::create_dir_all(&path).ok();
     path
 }
 
+// Beri tahu koneksi WebSocket bahwa state berubah; state tidak disalin,
+// tiap view dibuat dari state terbaru saat pertama kali dibutuhkan
 async fn broadcast_state(shared: &SharedState) {
-    let state = shared.state.read().await;
-    if let Ok(json) = serde_json::to_string(&*state) {
-        let _ = shared.broadcast_tx.send(json);
+    let _ = shared.broadcast_tx.send(Arc::new(StateFrame::default()));
+}
+
+fn create_token(shared: &SharedState, subject: &str, role: &str) -> Option<String> {
+    shared.auth.read().ok()?.create_token(subject, role)
+}
+
+fn request_bearer_token(req: &HttpRequest) -> Option<&str> {
+    req.headers()
+        .get("Authorization")?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+}
+
+fn decode_token(req: &HttpRequest, token: &str) -> Option<Claims> {
+    let shared = req.app_data::<web::Data<Arc<SharedState>>>()?;
+    let auth = shared.auth.read().ok()?;
+    auth.decode_token(token)
+}
+
+fn decode_request_token(req: &HttpRequest) -> Option<Claims> {
+    decode_token(req, request_bearer_token(req)?)
+}
+
+fn admin_role_of(claims: Claims) -> Option<(Claims, AdminRole)> {
+    let role = AdminRole::parse(&claims.role)?;
+    Some((claims, role))
+}
+
+fn verify_admin_token(req: &HttpRequest) -> Option<(Claims, AdminRole)> {
+    admin_role_of(decode_request_token(req)?)
+}
+
+// 401 jika bukan admin, 403 jika role admin tidak punya izin untuk route ini
+fn authorize_admin(req: &HttpRequest, permission: Permission) -> Result<Claims, HttpResponse> {
+    check_admin_permission(verify_admin_token(req), permission)
+}
+
+// Untuk link download: token boleh lewat header atau `?token=`
+fn authorize_admin_download(req: &HttpRequest, permission: Permission) -> Result<Claims, HttpResponse> {
+    let admin = request_header_or_query_token(req)
+        .and_then(|token| decode_token(req, &token))
+        .and_then(admin_role_of);
+    check_admin_permission(admin, permission)
+}
+
+fn check_admin_permission(admin: Option<(Claims, AdminRole)>, permission: Permission) -> Result<Claims, HttpResponse> {
+    match admin {
+        Some((claims, role)) if role.allows(permission) => Ok(claims),
+        Some(_) => Err(HttpResponse::Forbidden().json(serde_json::json!({"error": "Forbidden"}))),
+        None => Err(HttpResponse::Unauthorized().json(serde_json::json!({"error": "Unauthorized"}))),
     }
 }
 
-// Broadcast hanya timer state untuk performa lebih baik
-async fn broadcast_timer_only(shared: &SharedState) {
-    let state = shared.state.read().await;
-    let timer_msg = serde_json::json!({
-        "timer": state.timer,
-        "meja_list": state.meja_list,
-        "soal_files": state.soal_files,
-        "lomba_title": state.lomba_title
-    });
-    if let Ok(json) = serde_json::to_string(&timer_msg) {
-        let _ = shared.broadcast_tx.send(json);
+// Sama seperti StateView: kode meja hanya untuk role yang mengelola meja
+fn can_see_kode(claims: &Claims) -> bool {
+    AdminRole::parse(&claims.role).is_some_and(|role| role.allows(Permission::ManageLomba))
+}
+
+fn view_role_from_claims(claims: Claims) -> ViewRole {
+    if let Some(role) = AdminRole::parse(&claims.role) {
+        ViewRole::Admin(role)
+    } else if claims.role == "peserta" {
+        ViewRole::Peserta(claims.sub)
+    } else {
+        ViewRole::Public
     }
 }
 
-fn create_token(username: &str, role: &str) -> Option<String> {
-    let expiration = chrono::Utc::now()
-        .checked_add_signed(chrono::Duration::hours(24))
-        .expect("valid timestamp")
-        .timestamp() as usize;
+// Browser tidak bisa mengirim header Authorization saat membuka WebSocket
+// atau link download, jadi token juga diterima lewat query `?token=`
+fn request_header_or_query_token(req: &HttpRequest) -> Option<String> {
+    request_bearer_token(req).map(str::to_string).or_else(|| {
+        web::Query::<HashMap<String, String>>::from_query(req.query_string())
+            .ok()
+            .and_then(|query| query.get("token").cloned())
+    })
+}
 
-    let claims = Claims {
-        sub: username.to_string(),
-        exp: expiration,
-        role: role.to_string(),
-    };
+fn request_view_role(req: &HttpRequest) -> ViewRole {
+    request_header_or_query_token(req)
+        .and_then(|token| decode_token(req, &token))
+        .map(view_role_from_claims)
+        .unwrap_or(ViewRole::Public)
+}
 
-    encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(JWT_SECRET),
-    )
-    .ok()
-}
-
-fn verify_admin_token(req: &HttpRequest) -> bool {
-    let auth_header = req.headers().get("Authorization");
-    if let Some(auth_value) = auth_header {
-        if let Ok(auth_str) = auth_value.to_str() {
-            if auth_str.starts_with("Bearer ") {
-                let token = &auth_str[7..];
-                if let Ok(token_data) = decode::<Claims>(
-                    token,
-                    &DecodingKey::from_secret(JWT_SECRET),
-                    &Validation::default(),
-                ) {
-                    return token_data.claims.role == "admin";
-                }
-            }
+// Token peserta hanya berlaku untuk meja yang tertulis di `sub`
+fn verify_peserta_token(req: &HttpRequest, meja_id: &str) -> bool {
+    decode_request_token(req)
+        .map(|claims| claims.role == "peserta" && claims.sub == meja_id)
+        .unwrap_or(false)
+}
+
+fn client_ip(req: &HttpRequest) -> String {
+    req.peer_addr()
+        .map(|addr| addr.ip().to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+// Satu percobaan login yang sudah dipesan di limiter. Jika di-drop tanpa
+// `release` (kode salah, error, atau request dibatalkan) dicatat sebagai gagal.
+struct LoginAttempt<'a> {
+    shared: &'a SharedState,
+    keys: &'a [LimitKey],
+    released: bool,
+}
+
+impl LoginAttempt<'_> {
+    // Percobaan selesai tanpa gagal; counter `cleared` ikut direset
+    fn release(mut self, cleared: Option<&LimitKey>) {
+        self.released = true;
+        if let Ok(mut limiter) = self.shared.login_limiter.lock() {
+            limiter.release(self.keys, cleared);
+        }
+    }
+}
+
+impl Drop for LoginAttempt<'_> {
+    fn drop(&mut self) {
+        if self.released {
+            return;
+        }
+        if let Ok(mut limiter) = self.shared.login_limiter.lock() {
+            limiter.record_failure(self.keys, Utc::now());
         }
     }
-    false
+}
+
+// Tolak login selama salah satu key masih dalam masa lockout atau percobaan
+// yang sedang berjalan sudah menghabiskan jatahnya
+fn begin_login_attempt<'a>(shared: &'a SharedState, keys: &'a [LimitKey]) -> Result<LoginAttempt<'a>, HttpResponse> {
+    let Ok(mut limiter) = shared.login_limiter.lock() else {
+        return Err(HttpResponse::InternalServerError().json(serde_json::json!({"success": false, "error": "Internal error"})));
+    };
+    let remaining = match limiter.begin_attempt(keys, Utc::now()) {
+        Ok(()) => {
+            return Ok(LoginAttempt {
+                shared,
+                keys,
+                released: false,
+            })
+        }
+        Err(remaining) => remaining,
+    };
+    let retry_after = remaining.num_seconds().max(1);
+    Err(HttpResponse::TooManyRequests()
+        .insert_header(("Retry-After", retry_after.to_string()))
+        .json(serde_json::json!({
+            "success": false,
+            "error": format!("Terlalu banyak percobaan login. Coba lagi dalam {} detik", retry_after),
+            "retry_after_seconds": retry_after
+        })))
 }
 
 // === Auth Handlers ===
 
 async fn admin_login(
+    req: HttpRequest,
     shared: web::Data<Arc<SharedState>>,
     body: web::Json<LoginAdminRequest>,
 ) -> impl Responder {
+    let account_key = LimitKey::new(LimitScope::AdminUsername, &body.username);
+    let limit_keys = [LimitKey::new(LimitScope::AdminIp, &client_ip(&req)), account_key.clone()];
+    let attempt = match begin_login_attempt(&shared, &limit_keys) {
+        Ok(attempt) => attempt,
+        Err(resp) => return resp,
+    };
+
     let db = shared.db.lock().await;
 
-    let result: Result<String, _> = db.query_row(
-        "SELECT password_hash FROM admin WHERE username = ?1",
+    let result: Result<(String, String), _> = db.query_row(
+        "SELECT password_hash, role FROM admin WHERE username = ?1",
         params![body.username],
-        |row| row.get(0),
+        |row| Ok((row.get(0)?, row.get(1)?)),
     );
 
-    match result {
-        Ok(hash) => {
-            if verify(&body.password, &hash).unwrap_or(false) {
-                if let Some(token) = create_token(&body.username, "admin") {
-                    return HttpResponse::Ok().json(AuthResponse {
-                        success: true,
-                        token: Some(token),
-                        error: None,
-                    });
-                }
+    if let Ok((hash, role)) = result {
+        if verify(&body.password, &hash).unwrap_or(false) {
+            if let Some(token) = create_token(&shared, &body.username, &role) {
+                attempt.release(Some(&account_key));
+                return HttpResponse::Ok().json(AuthResponse {
+                    success: true,
+                    token: Some(token),
+                    error: None,
+                });
             }
         }
-        Err(_) => {}
     }
 
+    drop(attempt);
     HttpResponse::Unauthorized().json(AuthResponse {
         success: false,
         token: None,
@@ -452,8 +830,12 @@ async fn admin_login(
 }
 
 async fn verify_token(req: HttpRequest) -> impl Responder {
-    if verify_admin_token(&req) {
-        HttpResponse::Ok().json(serde_json::json!({"valid": true}))
+    if let Some((claims, role)) = verify_admin_token(&req) {
+        HttpResponse::Ok().json(serde_json::json!({
+            "valid": true,
+            "username": claims.sub,
+            "role": role
+        }))
     } else {
         HttpResponse::Unauthorized().json(serde_json::json!({"valid": false}))
     }
@@ -464,15 +846,15 @@ async fn change_password(
     shared: web::Data<Arc<SharedState>>,
     body: web::Json<ChangePasswordRequest>,
 ) -> impl Responder {
-    if !verify_admin_token(&req) {
+    let Some((claims, role)) = verify_admin_token(&req) else {
         return HttpResponse::Unauthorized().json(serde_json::json!({"error": "Unauthorized"}));
-    }
+    };
 
     let db = shared.db.lock().await;
 
     let result: Result<String, _> = db.query_row(
-        "SELECT password_hash FROM admin WHERE id = 1",
-        [],
+        "SELECT password_hash FROM admin WHERE username = ?1",
+        params![claims.sub],
         |row| row.get(0),
     );
 
@@ -481,10 +863,15 @@ async fn change_password(
             if verify(&body.old_password, &current_hash).unwrap_or(false) {
                 if let Ok(new_hash) = hash(&body.new_password, DEFAULT_COST) {
                     if db.execute(
-                        "UPDATE admin SET password_hash = ?1 WHERE id = 1",
-                        params![new_hash],
+                        "UPDATE admin SET password_hash = ?1 WHERE username = ?2",
+                        params![new_hash, claims.sub],
                     ).is_ok() {
-                        return HttpResponse::Ok().json(serde_json::json!({"success": true}));
+                        // Logout semua sesi lain; sesi ini mendapat token baru
+                        if let Ok(mut auth) = shared.auth.write() {
+                            auth.revoke_all_sessions(&db, &claims.sub);
+                        }
+                        let token = create_token(&shared, &claims.sub, role.as_str());
+                        return HttpResponse::Ok().json(serde_json::json!({"success": true, "token": token}));
                     }
                 }
             }
@@ -494,122 +881,406 @@ async fn change_password(
     }
 }
 
-// === Admin API Handlers ===
+async fn logout(req: HttpRequest, shared: web::Data<Arc<SharedState>>) -> impl Responder {
+    let Some(claims) = decode_request_token(&req) else {
+        return HttpResponse::Unauthorized().json(serde_json::json!({"error": "Unauthorized"}));
+    };
 
-async fn get_state(shared: web::Data<Arc<SharedState>>) -> impl Responder {
-    let state = shared.state.read().await;
-    HttpResponse::Ok().json(&*state)
+    let db = shared.db.lock().await;
+    if let Ok(mut auth) = shared.auth.write() {
+        auth.revoke(&db, &claims);
+    }
+
+    HttpResponse::Ok().json(serde_json::json!({"success": true}))
 }
 
-async fn generate_meja(
+async fn rotate_jwt_key(
     req: HttpRequest,
     shared: web::Data<Arc<SharedState>>,
-    body: web::Json<GenerateMejaRequest>,
+    body: Option<web::Json<RotateKeyRequest>>,
 ) -> impl Responder {
-    if !verify_admin_token(&req) {
-        return HttpResponse::Unauthorized().json(serde_json::json!({"error": "Unauthorized"}));
+    if let Err(resp) = authorize_admin(&req, Permission::ManageAccounts) {
+        return resp;
     }
 
-    let mut state = shared.state.write().await;
+    let revoke_existing = body.map(|b| b.revoke_existing).unwrap_or(false);
     let db = shared.db.lock().await;
+    let rotated = match shared.auth.write() {
+        Ok(mut auth) => auth.rotate_key(&db, revoke_existing).is_ok(),
+        Err(_) => false,
+    };
 
-    db.execute("DELETE FROM files", []).ok();
-    db.execute("DELETE FROM meja", []).ok();
-
-    state.meja_list.clear();
-
-    for i in 1..=body.jumlah {
-        let id = Uuid::new_v4().to_string();
-        let kode = generate_kode();
+    if rotated {
+        HttpResponse::Ok().json(serde_json::json!({"success": true}))
+    } else {
+        HttpResponse::InternalServerError().json(serde_json::json!({"error": "Gagal merotasi kunci"}))
+    }
+}
 
-        db.execute(
-            "INSERT INTO meja (id, nomor, kode) VALUES (?1, ?2, ?3)",
-            params![id, i, kode],
-        ).ok();
+// === Admin Account Handlers ===
 
-        let meja = Meja {
-            id: id.clone(),
-            nomor: i,
-            kode,
-            nama_peserta: None,
-            files: vec![],
-            last_upload: None,
-        };
-        state.meja_list.insert(id, meja);
+async fn list_admins(
+    req: HttpRequest,
+    shared: web::Data<Arc<SharedState>>,
+) -> impl Responder {
+    if let Err(resp) = authorize_admin(&req, Permission::ManageAccounts) {
+        return resp;
     }
 
-    drop(db);
-    drop(state);
-    broadcast_state(&shared).await;
+    let db = shared.db.lock().await;
+    let mut admins: Vec<AdminUser> = vec![];
+    if let Ok(mut stmt) = db.prepare("SELECT id, username, role FROM admin ORDER BY id") {
+        if let Ok(rows) = stmt.query_map([], |row| {
+            let role: String = row.get(2)?;
+            Ok(AdminUser {
+                id: row.get(0)?,
+                username: row.get(1)?,
+                role: AdminRole::parse(&role).unwrap_or(AdminRole::Juri),
+            })
+        }) {
+            admins = rows.flatten().collect();
+        }
+    }
 
-    HttpResponse::Ok().json(serde_json::json!({"success": true}))
+    HttpResponse::Ok().json(admins)
 }
 
-async fn set_timer(
+async fn create_admin(
     req: HttpRequest,
     shared: web::Data<Arc<SharedState>>,
-    body: web::Json<SetTimerRequest>,
+    body: web::Json<CreateAdminRequest>,
 ) -> impl Responder {
-    if !verify_admin_token(&req) {
-        return HttpResponse::Unauthorized().json(serde_json::json!({"error": "Unauthorized"}));
+    if let Err(resp) = authorize_admin(&req, Permission::ManageAccounts) {
+        return resp;
     }
 
-    let mut state = shared.state.write().await;
-    state.timer.duration_seconds = body.duration_minutes * 60;
-    state.timer.remaining_seconds = state.timer.duration_seconds;
-    state.timer.is_running = false;
-    state.timer.started_at = None;
+    let username = body.username.trim();
+    if username.is_empty() || body.password.is_empty() {
+        return HttpResponse::BadRequest().json(serde_json::json!({"error": "Username dan password wajib diisi"}));
+    }
+
+    let Ok(password_hash) = hash(&body.password, DEFAULT_COST) else {
+        return HttpResponse::InternalServerError().json(serde_json::json!({"error": "Gagal membuat hash password"}));
+    };
 
     let db = shared.db.lock().await;
-    save_timer_to_db(&db, &state.timer);
+    match db.execute(
+        "INSERT INTO admin (username, password_hash, role) VALUES (?1, ?2, ?3)",
+        params![username, password_hash, body.role.as_str()],
+    ) {
+        Ok(_) => HttpResponse::Ok().json(AdminUser {
+            id: db.last_insert_rowid(),
+            username: username.to_string(),
+            role: body.role,
+        }),
+        Err(_) => HttpResponse::Conflict().json(serde_json::json!({"error": "Username sudah dipakai"})),
+    }
+}
 
-    drop(db);
-    drop(state);
-    broadcast_state(&shared).await;
+// Jumlah superadmin selain `except_id`, agar akun superadmin terakhir tidak hilang
+fn count_other_superadmins(conn: &Connection, except_id: i64) -> i64 {
+    conn.query_row(
+        "SELECT COUNT(*) FROM admin WHERE role = 'superadmin' AND id != ?1",
+        params![except_id],
+        |row| row.get(0),
+    )
+    .unwrap_or(0)
+}
 
-    HttpResponse::Ok().json(serde_json::json!({"success": true}))
+// Role dan password diubah dalam satu transaksi
+fn apply_admin_update(
+    conn: &Connection,
+    admin_id: i64,
+    role: Option<AdminRole>,
+    password_hash: Option<&str>,
+) -> rusqlite::Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    if let Some(role) = role {
+        tx.execute("UPDATE admin SET role = ?1 WHERE id = ?2", params![role.as_str(), admin_id])?;
+    }
+    if let Some(password_hash) = password_hash {
+        tx.execute("UPDATE admin SET password_hash = ?1 WHERE id = ?2", params![password_hash, admin_id])?;
+    }
+    tx.commit()
 }
 
-async fn start_timer(
+async fn update_admin(
     req: HttpRequest,
     shared: web::Data<Arc<SharedState>>,
+    path: web::Path<i64>,
+    body: web::Json<UpdateAdminRequest>,
 ) -> impl Responder {
-    if !verify_admin_token(&req) {
-        return HttpResponse::Unauthorized().json(serde_json::json!({"error": "Unauthorized"}));
+    if let Err(resp) = authorize_admin(&req, Permission::ManageAccounts) {
+        return resp;
     }
 
-    let mut state = shared.state.write().await;
-    if !state.timer.is_running && state.timer.remaining_seconds > 0 {
-        state.timer.is_running = true;
-        state.timer.started_at = Some(Utc::now());
+    let admin_id = path.into_inner();
+    let db = shared.db.lock().await;
 
-        let db = shared.db.lock().await;
-        save_timer_to_db(&db, &state.timer);
-    }
+    let target: Option<(String, String)> = db
+        .query_row(
+            "SELECT username, role FROM admin WHERE id = ?1",
+            params![admin_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .ok();
+    let Some((username, current_role)) = target else {
+        return HttpResponse::NotFound().json(serde_json::json!({"error": "Admin not found"}));
+    };
 
+    // Seluruh request divalidasi dulu supaya tidak ada perubahan yang setengah jalan
+    if let Some(role) = body.role {
+        if current_role == AdminRole::Superadmin.as_str()
+            && role != AdminRole::Superadmin
+            && count_other_superadmins(&db, admin_id) == 0
+        {
+            return HttpResponse::BadRequest().json(serde_json::json!({"error": "Minimal harus ada satu superadmin"}));
+        }
+    }
+    let password_hash = match body.password.as_deref() {
+        Some("") => {
+            return HttpResponse::BadRequest().json(serde_json::json!({"error": "Password tidak boleh kosong"}));
+        }
+        Some(password) => match hash(password, DEFAULT_COST) {
+            Ok(password_hash) => Some(password_hash),
+            Err(_) => {
+                return HttpResponse::InternalServerError().json(serde_json::json!({"error": "Gagal membuat hash password"}));
+            }
+        },
+        None => None,
+    };
+
+    if let Err(e) = apply_admin_update(&db, admin_id, body.role, password_hash.as_deref()) {
+        eprintln!("Gagal mengubah admin {}: {}", admin_id, e);
+        return HttpResponse::InternalServerError().json(serde_json::json!({"error": "Database error"}));
+    }
+
+    // Token lama masih membawa role/password lama, jadi paksa login ulang
+    if let Ok(mut auth) = shared.auth.write() {
+        auth.revoke_all_sessions(&db, &username);
+    }
+
+    HttpResponse::Ok().json(serde_json::json!({"success": true}))
+}
+
+async fn delete_admin(
+    req: HttpRequest,
+    shared: web::Data<Arc<SharedState>>,
+    path: web::Path<i64>,
+) -> impl Responder {
+    let claims = match authorize_admin(&req, Permission::ManageAccounts) {
+        Ok(claims) => claims,
+        Err(resp) => return resp,
+    };
+
+    let admin_id = path.into_inner();
+    let db = shared.db.lock().await;
+
+    let target: Option<(String, String)> = db
+        .query_row(
+            "SELECT username, role FROM admin WHERE id = ?1",
+            params![admin_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .ok();
+    let Some((username, role)) = target else {
+        return HttpResponse::NotFound().json(serde_json::json!({"error": "Admin not found"}));
+    };
+
+    if username == claims.sub {
+        return HttpResponse::BadRequest().json(serde_json::json!({"error": "Tidak bisa menghapus akun sendiri"}));
+    }
+    if role == AdminRole::Superadmin.as_str() && count_other_superadmins(&db, admin_id) == 0 {
+        return HttpResponse::BadRequest().json(serde_json::json!({"error": "Minimal harus ada satu superadmin"}));
+    }
+
+    db.execute("DELETE FROM admin WHERE id = ?1", params![admin_id]).ok();
+    if let Ok(mut auth) = shared.auth.write() {
+        auth.revoke_all_sessions(&db, &username);
+    }
+
+    HttpResponse::Ok().json(serde_json::json!({"success": true}))
+}
+
+// === Login Lockout Handlers ===
+
+async fn list_lockouts(
+    req: HttpRequest,
+    shared: web::Data<Arc<SharedState>>,
+) -> impl Responder {
+    if let Err(resp) = authorize_admin(&req, Permission::ReadSubmissions) {
+        return resp;
+    }
+
+    let Ok(limiter) = shared.login_limiter.lock() else {
+        return HttpResponse::InternalServerError().json(serde_json::json!({"error": "Limiter error"}));
+    };
+    HttpResponse::Ok().json(serde_json::json!({
+        "active": limiter.active_lockouts(Utc::now()),
+        "events": limiter.recent_events()
+    }))
+}
+
+async fn lift_lockout(
+    req: HttpRequest,
+    shared: web::Data<Arc<SharedState>>,
+    body: web::Json<LimitKey>,
+) -> impl Responder {
+    let claims = match authorize_admin(&req, Permission::ManageLockouts) {
+        Ok(claims) => claims,
+        Err(resp) => return resp,
+    };
+
+    let lifted = shared
+        .login_limiter
+        .lock()
+        .map(|mut limiter| limiter.lift(&body, &claims.sub))
+        .unwrap_or(false);
+
+    if lifted {
+        HttpResponse::Ok().json(serde_json::json!({"success": true}))
+    } else {
+        HttpResponse::NotFound().json(serde_json::json!({"error": "Lockout not found"}))
+    }
+}
+
+// === Admin API Handlers ===
+
+async fn get_state(req: HttpRequest, shared: web::Data<Arc<SharedState>>) -> impl Responder {
+    let role = request_view_role(&req);
+    let state = shared.state.read().await;
+    HttpResponse::Ok().json(StateView::new(&state, &role))
+}
+
+async fn generate_meja(
+    req: HttpRequest,
+    shared: web::Data<Arc<SharedState>>,
+    body: web::Json<GenerateMejaRequest>,
+) -> impl Responder {
+    if let Err(resp) = authorize_admin(&req, Permission::ManageLomba) {
+        return resp;
+    }
+
+    let mut state = shared.state.write().await;
+    let db = shared.db.lock().await;
+
+    resumable::delete_all_sessions(&db);
+    db.execute("DELETE FROM files", []).ok();
+    db.execute("DELETE FROM file_audit", []).ok();
+    db.execute("DELETE FROM time_extensions", []).ok();
+    db.execute("DELETE FROM meja", []).ok();
+
+    state.meja_list.clear();
+
+    for i in 1..=body.jumlah {
+        let id = Uuid::new_v4().to_string();
+        let kode = generate_kode();
+
+        db.execute(
+            "INSERT INTO meja (id, nomor, kode) VALUES (?1, ?2, ?3)",
+            params![id, i, kode],
+        ).ok();
+
+        let meja = Meja {
+            id: id.clone(),
+            nomor: i,
+            kode,
+            nama_peserta: None,
+            files: vec![],
+            last_upload: None,
+            submitted_at: None,
+            extensions: vec![],
+        };
+        state.meja_list.insert(id, meja);
+    }
+
+    drop(db);
     drop(state);
     broadcast_state(&shared).await;
 
     HttpResponse::Ok().json(serde_json::json!({"success": true}))
 }
 
-async fn pause_timer(
+async fn set_upload_rules(
     req: HttpRequest,
     shared: web::Data<Arc<SharedState>>,
+    body: web::Json<UploadRules>,
 ) -> impl Responder {
-    if !verify_admin_token(&req) {
-        return HttpResponse::Unauthorized().json(serde_json::json!({"error": "Unauthorized"}));
+    if let Err(resp) = authorize_admin(&req, Permission::ManageLomba) {
+        return resp;
     }
+    let upload_rules = match body.into_inner().normalize() {
+        Ok(upload_rules) => upload_rules,
+        Err(e) => return HttpResponse::BadRequest().json(serde_json::json!({"error": e})),
+    };
 
     let mut state = shared.state.write().await;
-    if state.timer.is_running {
-        if let Some(started) = state.timer.started_at {
-            let elapsed = Utc::now().signed_duration_since(started).num_seconds();
-            state.timer.remaining_seconds = (state.timer.remaining_seconds - elapsed).max(0);
-        }
-        state.timer.is_running = false;
-        state.timer.started_at = None;
+    if let Err(e) = rules::save_rules(&*shared.db.lock().await, &upload_rules) {
+        eprintln!("Gagal menyimpan aturan upload: {}", e);
+        return HttpResponse::InternalServerError().json(serde_json::json!({"error": "Gagal menyimpan aturan upload"}));
+    }
+    state.upload_rules = upload_rules.clone();
+    drop(state);
+    broadcast_state(&shared).await;
+
+    HttpResponse::Ok().json(serde_json::json!({"success": true, "rules": upload_rules}))
+}
+
+async fn set_timer(
+    req: HttpRequest,
+    shared: web::Data<Arc<SharedState>>,
+    body: web::Json<SetTimerRequest>,
+) -> impl Responder {
+    if let Err(resp) = authorize_admin(&req, Permission::ControlTimer) {
+        return resp;
+    }
+    if !(0..=timer::MAX_DURATION_SECONDS / 60).contains(&body.duration_minutes) {
+        return HttpResponse::BadRequest().json(serde_json::json!({"error": "Durasi harus antara 0 dan 7 hari"}));
+    }
+
+    let mut state = shared.state.write().await;
+    state.timer.set_duration(body.duration_minutes * 60);
+
+    let db = shared.db.lock().await;
+    save_timer_to_db(&db, &state.timer);
+
+    drop(db);
+    drop(state);
+    broadcast_state(&shared).await;
+
+    HttpResponse::Ok().json(serde_json::json!({"success": true}))
+}
+
+async fn start_timer(
+    req: HttpRequest,
+    shared: web::Data<Arc<SharedState>>,
+) -> impl Responder {
+    if let Err(resp) = authorize_admin(&req, Permission::ControlTimer) {
+        return resp;
+    }
+
+    let mut state = shared.state.write().await;
+    if state.timer.start(Utc::now()) {
+        let db = shared.db.lock().await;
+        save_timer_to_db(&db, &state.timer);
+    }
+
+    drop(state);
+    broadcast_state(&shared).await;
+
+    HttpResponse::Ok().json(serde_json::json!({"success": true}))
+}
+
+async fn pause_timer(
+    req: HttpRequest,
+    shared: web::Data<Arc<SharedState>>,
+) -> impl Responder {
+    if let Err(resp) = authorize_admin(&req, Permission::ControlTimer) {
+        return resp;
+    }
 
+    let mut state = shared.state.write().await;
+    if state.timer.pause(Utc::now()) {
         let db = shared.db.lock().await;
         save_timer_to_db(&db, &state.timer);
     }
@@ -624,14 +1295,12 @@ async fn reset_timer(
     req: HttpRequest,
     shared: web::Data<Arc<SharedState>>,
 ) -> impl Responder {
-    if !verify_admin_token(&req) {
-        return HttpResponse::Unauthorized().json(serde_json::json!({"error": "Unauthorized"}));
+    if let Err(resp) = authorize_admin(&req, Permission::ControlTimer) {
+        return resp;
     }
 
     let mut state = shared.state.write().await;
-    state.timer.remaining_seconds = state.timer.duration_seconds;
-    state.timer.is_running = false;
-    state.timer.started_at = None;
+    state.timer.reset();
 
     let db = shared.db.lock().await;
     save_timer_to_db(&db, &state.timer);
@@ -648,23 +1317,44 @@ async fn adjust_timer(
     shared: web::Data<Arc<SharedState>>,
     body: web::Json<AdjustTimerRequest>,
 ) -> impl Responder {
-    if !verify_admin_token(&req) {
-        return HttpResponse::Unauthorized().json(serde_json::json!({"error": "Unauthorized"}));
+    if let Err(resp) = authorize_admin(&req, Permission::ControlTimer) {
+        return resp;
+    }
+    if !(-timer::MAX_DURATION_SECONDS..=timer::MAX_DURATION_SECONDS).contains(&body.seconds) {
+        return HttpResponse::BadRequest().json(serde_json::json!({"error": "Perubahan waktu maksimal 7 hari"}));
     }
 
     let mut state = shared.state.write().await;
+    state.timer.adjust(body.seconds, Utc::now());
 
-    if state.timer.is_running {
-        if let Some(started) = state.timer.started_at {
-            let elapsed = chrono::Utc::now().signed_duration_since(started).num_seconds();
-            let current_remaining = state.timer.duration_seconds - elapsed;
-            state.timer.duration_seconds = current_remaining + body.seconds + elapsed;
-        }
-    } else {
-        state.timer.remaining_seconds = (state.timer.remaining_seconds + body.seconds).max(0);
-        state.timer.duration_seconds = state.timer.remaining_seconds;
+    let db = shared.db.lock().await;
+    save_timer_to_db(&db, &state.timer);
+
+    drop(db);
+    drop(state);
+    broadcast_state(&shared).await;
+
+    HttpResponse::Ok().json(serde_json::json!({"success": true}))
+}
+
+async fn set_grace_period(
+    req: HttpRequest,
+    shared: web::Data<Arc<SharedState>>,
+    body: web::Json<SetGraceRequest>,
+) -> impl Responder {
+    if let Err(resp) = authorize_admin(&req, Permission::ControlTimer) {
+        return resp;
+    }
+    if body.grace_minutes < 0 {
+        return HttpResponse::BadRequest().json(serde_json::json!({"error": "Masa tenggang tidak boleh negatif"}));
+    }
+    if body.grace_minutes > timer::MAX_DURATION_SECONDS / 60 {
+        return HttpResponse::BadRequest().json(serde_json::json!({"error": "Masa tenggang maksimal 7 hari"}));
     }
 
+    let mut state = shared.state.write().await;
+    state.timer.grace_seconds = body.grace_minutes * 60;
+
     let db = shared.db.lock().await;
     save_timer_to_db(&db, &state.timer);
 
@@ -675,19 +1365,91 @@ async fn adjust_timer(
     HttpResponse::Ok().json(serde_json::json!({"success": true}))
 }
 
+async fn grant_extension(
+    req: HttpRequest,
+    shared: web::Data<Arc<SharedState>>,
+    path: web::Path<String>,
+    body: web::Json<GrantExtensionRequest>,
+) -> impl Responder {
+    let claims = match authorize_admin(&req, Permission::ControlTimer) {
+        Ok(claims) => claims,
+        Err(resp) => return resp,
+    };
+    let meja_id = path.into_inner();
+    let reason = body.reason.trim();
+    if body.seconds <= 0 {
+        return HttpResponse::BadRequest().json(serde_json::json!({"error": "Tambahan waktu harus lebih dari 0"}));
+    }
+    if body.seconds > extensions::MAX_EXTENSION_SECONDS {
+        return HttpResponse::BadRequest().json(serde_json::json!({"error": "Tambahan waktu maksimal 24 jam"}));
+    }
+    if reason.is_empty() {
+        return HttpResponse::BadRequest().json(serde_json::json!({"error": "Alasan tambahan waktu wajib diisi"}));
+    }
+
+    let mut state = shared.state.write().await;
+    let Some(meja) = state.meja_list.get_mut(&meja_id) else {
+        return HttpResponse::NotFound().json(serde_json::json!({"error": "Meja not found"}));
+    };
+    let extension = match extensions::grant(&*shared.db.lock().await, &meja_id, body.seconds, reason, &claims.sub) {
+        Ok(extension) => extension,
+        Err(e) => {
+            eprintln!("Gagal menyimpan tambahan waktu meja {}: {}", meja.nomor, e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({"error": "Gagal menyimpan tambahan waktu"}));
+        }
+    };
+    meja.extensions.push(extension.clone());
+
+    drop(state);
+    broadcast_state(&shared).await;
+
+    HttpResponse::Ok().json(serde_json::json!({"success": true, "extension": extension}))
+}
+
+async fn revoke_extension(
+    req: HttpRequest,
+    shared: web::Data<Arc<SharedState>>,
+    path: web::Path<(String, i64)>,
+) -> impl Responder {
+    if let Err(resp) = authorize_admin(&req, Permission::ControlTimer) {
+        return resp;
+    }
+    let (meja_id, extension_id) = path.into_inner();
+
+    let mut state = shared.state.write().await;
+    let Some(meja) = state.meja_list.get_mut(&meja_id) else {
+        return HttpResponse::NotFound().json(serde_json::json!({"error": "Meja not found"}));
+    };
+    match extensions::revoke(&*shared.db.lock().await, &meja_id, extension_id) {
+        Ok(true) => meja.extensions.retain(|e| e.id != extension_id),
+        Ok(false) => {
+            return HttpResponse::NotFound().json(serde_json::json!({"error": "Tambahan waktu tidak ditemukan"}));
+        }
+        Err(e) => {
+            eprintln!("Gagal menghapus tambahan waktu {}: {}", extension_id, e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({"error": "Gagal menghapus tambahan waktu"}));
+        }
+    }
+
+    drop(state);
+    broadcast_state(&shared).await;
+
+    HttpResponse::Ok().json(serde_json::json!({"success": true}))
+}
+
 async fn upload_soal(
     req: HttpRequest,
     shared: web::Data<Arc<SharedState>>,
     mut payload: Multipart,
 ) -> impl Responder {
-    if !verify_admin_token(&req) {
-        return HttpResponse::Unauthorized().json(serde_json::json!({"error": "Unauthorized"}));
+    if let Err(resp) = authorize_admin(&req, Permission::ManageLomba) {
+        return resp;
     }
 
     while let Ok(Some(mut field)) = payload.try_next().await {
         let content_disposition = field.content_disposition();
         let filename = content_disposition
-            .and_then(|cd| cd.get_filename().map(|f| sanitize_filename::sanitize(f)))
+            .and_then(|cd| cd.get_filename().map(sanitize_filename::sanitize))
             .unwrap_or_else(|| Uuid::new_v4().to_string());
 
         let filepath = get_soal_path().join(&filename);
@@ -713,10 +1475,8 @@ async fn upload_soal(
             }
         }
         // Write remaining buffer
-        if !buffer.is_empty() {
-            if file.write_all(&buffer).await.is_err() {
-                return HttpResponse::InternalServerError().json(serde_json::json!({"error": "Failed to write file"}));
-            }
+        if !buffer.is_empty() && file.write_all(&buffer).await.is_err() {
+            return HttpResponse::InternalServerError().json(serde_json::json!({"error": "Failed to write file"}));
         }
         file.flush().await.ok();
 
@@ -751,8 +1511,8 @@ async fn delete_soal(
     shared: web::Data<Arc<SharedState>>,
     path: web::Path<String>,
 ) -> impl Responder {
-    if !verify_admin_token(&req) {
-        return HttpResponse::Unauthorized().json(serde_json::json!({"error": "Unauthorized"}));
+    if let Err(resp) = authorize_admin(&req, Permission::ManageLomba) {
+        return resp;
     }
 
     let soal_id = path.into_inner();
@@ -778,24 +1538,35 @@ async fn export_meja(
     req: HttpRequest,
     shared: web::Data<Arc<SharedState>>,
 ) -> impl Responder {
-    if !verify_admin_token(&req) {
-        return HttpResponse::Unauthorized().json(serde_json::json!({"error": "Unauthorized"}));
-    }
+    let show_kode = match authorize_admin(&req, Permission::ReadSubmissions) {
+        Ok(claims) => can_see_kode(&claims),
+        Err(resp) => return resp,
+    };
 
     let state = shared.state.read().await;
     let mut meja_list: Vec<&Meja> = state.meja_list.values().collect();
     meja_list.sort_by_key(|m| m.nomor);
 
-    let mut csv = String::from("Nomor Meja,Kode,Nama Peserta,Jumlah File,Status\n");
+    // Deadline kosong selama timer dijeda atau belum dimulai
+    let mut csv = String::from(
+        "Nomor Meja,Kode,Nama Peserta,Jumlah File,Status,Waktu Selesai,Tambahan Waktu (detik),Deadline,Terlambat (detik)\n",
+    );
     for meja in &meja_list {
-        let status = if meja.files.is_empty() { "Belum Upload" } else { "Sudah Upload" };
         csv.push_str(&format!(
-            "Meja {},\"{}\",\"{}\",{},{}\n",
+            "Meja {},\"{}\",\"{}\",{},{},{},{},{},{}\n",
             meja.nomor,
-            meja.kode,
+            if show_kode { meja.kode.as_str() } else { "-" },
             meja.nama_peserta.as_deref().unwrap_or("-"),
             meja.files.len(),
-            status
+            meja.status(),
+            meja.submitted_at.map(|t| t.to_rfc3339()).unwrap_or_else(|| "-".to_string()),
+            meja.extension_seconds(),
+            state
+                .timer
+                .deadline(meja.extension_seconds())
+                .map(|t| t.to_rfc3339())
+                .unwrap_or_else(|| "-".to_string()),
+            meja.seconds_late().map(|s| s.to_string()).unwrap_or_else(|| "-".to_string())
         ));
     }
 
@@ -809,18 +1580,24 @@ async fn export_meja_json(
     req: HttpRequest,
     shared: web::Data<Arc<SharedState>>,
 ) -> impl Responder {
-    if !verify_admin_token(&req) {
-        return HttpResponse::Unauthorized().json(serde_json::json!({"error": "Unauthorized"}));
-    }
+    let show_kode = match authorize_admin(&req, Permission::ReadSubmissions) {
+        Ok(claims) => can_see_kode(&claims),
+        Err(resp) => return resp,
+    };
 
     let state = shared.state.read().await;
     let mut meja_list: Vec<_> = state.meja_list.values().map(|m| {
         serde_json::json!({
             "nomor": m.nomor,
-            "kode": m.kode,
+            "kode": show_kode.then_some(&m.kode),
             "nama_peserta": m.nama_peserta,
             "jumlah_file": m.files.len(),
-            "status": if m.files.is_empty() { "Belum Upload" } else { "Sudah Upload" }
+            "status": m.status(),
+            "submitted_at": m.submitted_at,
+            "extension_seconds": m.extension_seconds(),
+            "extensions": m.extensions,
+            "deadline": state.timer.deadline(m.extension_seconds()),
+            "seconds_late": m.seconds_late()
         })
     }).collect();
     meja_list.sort_by_key(|m| m["nomor"].as_u64().unwrap_or(0));
@@ -828,23 +1605,96 @@ async fn export_meja_json(
     HttpResponse::Ok().json(meja_list)
 }
 
+// ZIP berisi semua upload per meja beserta manifest.csv, di-stream langsung
+// ke client. Filter: `meja` (satu nomor), `from`/`to` (rentang nomor), `latest`
+// (hanya versi yang dinilai: final atau terbaru).
+async fn export_submissions_zip(
+    req: HttpRequest,
+    shared: web::Data<Arc<SharedState>>,
+    query: web::Query<BundleQuery>,
+) -> impl Responder {
+    if let Err(resp) = authorize_admin_download(&req, Permission::ReadSubmissions) {
+        return resp;
+    }
+
+    let (from, to) = match query.meja {
+        Some(nomor) => (nomor, nomor),
+        None => (query.from.unwrap_or(0), query.to.unwrap_or(u32::MAX)),
+    };
+    if from > to {
+        return HttpResponse::BadRequest().json(serde_json::json!({"error": "Rentang meja tidak valid"}));
+    }
+
+    let items: Vec<bundle::BundleItem> = {
+        let state = shared.state.read().await;
+        let mut meja_list: Vec<&Meja> = state
+            .meja_list
+            .values()
+            .filter(|m| (from..=to).contains(&m.nomor))
+            .collect();
+        meja_list.sort_by_key(|m| m.nomor);
+        meja_list
+            .into_iter()
+            .flat_map(|m| bundle::meja_items(m, query.latest))
+            .collect()
+    };
+
+    let (writer, reader) = tokio::io::duplex(256 * 1024);
+    actix_web::rt::spawn(async move {
+        if let Err(e) = bundle::write_bundle(writer, items).await {
+            eprintln!("Export ZIP gagal: {}", e);
+        }
+    });
+
+    let filename = match query.meja {
+        Some(nomor) => format!("submission_meja_{}.zip", nomor),
+        None => "submission_semua_meja.zip".to_string(),
+    };
+    HttpResponse::Ok()
+        .content_type("application/zip")
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(filename)],
+        })
+        .streaming(tokio_util::io::ReaderStream::new(reader))
+}
+
 // === Participant API Handlers ===
 
 async fn login_peserta(
+    req: HttpRequest,
     shared: web::Data<Arc<SharedState>>,
     body: web::Json<LoginRequest>,
 ) -> impl Responder {
+    // Kode peserta adalah akunnya sendiri, jadi yang dibatasi cukup per IP.
+    // Login berhasil sengaja tidak mereset counter IP agar kode milik sendiri
+    // tidak bisa dipakai untuk membuka blokir tebakan kode meja lain.
+    let limit_keys = [LimitKey::new(LimitScope::PesertaIp, &client_ip(&req))];
+    let attempt = match begin_login_attempt(&shared, &limit_keys) {
+        Ok(attempt) => attempt,
+        Err(resp) => return resp,
+    };
+
     let state = shared.state.read().await;
 
     for meja in state.meja_list.values() {
         if meja.kode == body.kode {
-            return HttpResponse::Ok().json(serde_json::json!({
-                "success": true,
-                "meja": meja
+            attempt.release(None);
+            if let Some(token) = create_token(&shared, &meja.id, "peserta") {
+                return HttpResponse::Ok().json(serde_json::json!({
+                    "success": true,
+                    "meja": meja,
+                    "token": token
+                }));
+            }
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "success": false,
+                "error": "Gagal membuat token"
             }));
         }
     }
 
+    drop(attempt);
     HttpResponse::Unauthorized().json(serde_json::json!({
         "success": false,
         "error": "Kode tidak valid"
@@ -852,11 +1702,15 @@ async fn login_peserta(
 }
 
 async fn update_peserta(
+    req: HttpRequest,
     shared: web::Data<Arc<SharedState>>,
     path: web::Path<String>,
     body: web::Json<UpdatePesertaRequest>,
 ) -> impl Responder {
     let meja_id = path.into_inner();
+    if !verify_peserta_token(&req, &meja_id) {
+        return HttpResponse::Unauthorized().json(serde_json::json!({"error": "Unauthorized"}));
+    }
     let mut state = shared.state.write().await;
 
     if let Some(meja) = state.meja_list.get_mut(&meja_id) {
@@ -877,141 +1731,1219 @@ async fn update_peserta(
     HttpResponse::NotFound().json(serde_json::json!({"error": "Meja not found"}))
 }
 
-async fn upload_file(
+// Peserta menyatakan selesai: himpunan file dibekukan sampai admin membuka kembali
+async fn submit_meja(
+    req: HttpRequest,
     shared: web::Data<Arc<SharedState>>,
     path: web::Path<String>,
-    mut payload: Multipart,
 ) -> impl Responder {
     let meja_id = path.into_inner();
+    if !verify_peserta_token(&req, &meja_id) {
+        return HttpResponse::Unauthorized().json(serde_json::json!({"error": "Unauthorized"}));
+    }
 
-    {
-        let state = shared.state.read().await;
-        if !state.meja_list.contains_key(&meja_id) {
-            return HttpResponse::NotFound().json(serde_json::json!({"error": "Meja not found"}));
-        }
+    let mut state = shared.state.write().await;
+    if let Err(resp) = check_upload_allowed(&state, &meja_id) {
+        return resp;
+    }
+    let Some(meja) = state.meja_list.get_mut(&meja_id) else {
+        return HttpResponse::NotFound().json(serde_json::json!({"error": "Meja not found"}));
+    };
+    if meja.files.is_empty() {
+        return HttpResponse::BadRequest().json(serde_json::json!({"error": "Belum ada file yang diupload"}));
+    }
 
-        let remaining = if state.timer.is_running {
-            if let Some(started) = state.timer.started_at {
-                let elapsed = Utc::now().signed_duration_since(started).num_seconds();
-                (state.timer.duration_seconds - elapsed).max(0)
-            } else {
-                state.timer.remaining_seconds
-            }
-        } else {
-            state.timer.remaining_seconds
-        };
+    let submitted_at = Utc::now();
+    let db = shared.db.lock().await;
+    if let Err(e) = db.execute(
+        "UPDATE meja SET submitted_at = ?1 WHERE id = ?2",
+        params![submitted_at.to_rfc3339(), meja_id],
+    ) {
+        eprintln!("Gagal menyimpan final submit meja {}: {}", meja.nomor, e);
+        return HttpResponse::InternalServerError().json(serde_json::json!({"error": "Gagal menyimpan final submit"}));
+    }
+    meja.submitted_at = Some(submitted_at);
 
-        if remaining <= 0 && state.timer.duration_seconds > 0 {
-            return HttpResponse::Forbidden().json(serde_json::json!({
-                "error": "Waktu telah habis!!",
-                "time_expired": true
-            }));
+    drop(db);
+    drop(state);
+    broadcast_state(&shared).await;
+
+    HttpResponse::Ok().json(serde_json::json!({"success": true, "submitted_at": submitted_at}))
+}
+
+async fn reopen_meja(
+    req: HttpRequest,
+    shared: web::Data<Arc<SharedState>>,
+    path: web::Path<String>,
+) -> impl Responder {
+    if let Err(resp) = authorize_admin(&req, Permission::ManageLomba) {
+        return resp;
+    }
+    let meja_id = path.into_inner();
+
+    let mut state = shared.state.write().await;
+    let Some(meja) = state.meja_list.get_mut(&meja_id) else {
+        return HttpResponse::NotFound().json(serde_json::json!({"error": "Meja not found"}));
+    };
+
+    let db = shared.db.lock().await;
+    db.execute("UPDATE meja SET submitted_at = NULL WHERE id = ?1", params![meja_id]).ok();
+    meja.submitted_at = None;
+
+    drop(db);
+    drop(state);
+    broadcast_state(&shared).await;
+
+    HttpResponse::Ok().json(serde_json::json!({"success": true}))
+}
+
+// Meja harus ada dan belum final submit
+fn check_meja_open(state: &AppState, meja_id: &str) -> Result<(), HttpResponse> {
+    let Some(meja) = state.meja_list.get(meja_id) else {
+        return Err(HttpResponse::NotFound().json(serde_json::json!({"error": "Meja not found"})));
+    };
+    if meja.submitted_at.is_some() {
+        return Err(HttpResponse::Conflict().json(serde_json::json!({
+            "error": "Meja sudah final submit, minta admin membuka kembali untuk mengubah file",
+            "submitted": true
+        })));
+    }
+    Ok(())
+}
+
+// Meja terbuka dan deadline meja belum lewat (atau masih dalam masa
+// tenggang); dicek di awal setiap request upload
+fn check_upload_allowed(state: &AppState, meja_id: &str) -> Result<(), HttpResponse> {
+    check_meja_open(state, meja_id)?;
+
+    let extension_seconds = state.meja_list.get(meja_id).map_or(0, Meja::extension_seconds);
+    if !state.timer.accepts_upload(Utc::now(), extension_seconds) {
+        return Err(HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Waktu telah habis!!",
+            "time_expired": true
+        })));
+    }
+    Ok(())
+}
+
+fn file_too_large_response() -> HttpResponse {
+    let max_size_mb = settings().max_file_size_mb();
+    HttpResponse::PayloadTooLarge().json(serde_json::json!({
+        "error": format!("Ukuran file melebihi batas maksimal {}MB", max_size_mb),
+        "max_size_mb": max_size_mb
+    }))
+}
+
+// Blob yang sudah ada di folder meja dan tinggal dicatat
+struct StoredBlob {
+    file_id: String,
+    path: PathBuf,
+    size: u64,
+    sha256: String,
+    // Saat byte terakhir diterima, untuk menentukan keterlambatan
+    received_at: DateTime<Utc>,
+}
+
+fn record_uploaded_file(
+    conn: &Connection,
+    state: &AppState,
+    meja_id: &str,
+    filename: &str,
+    blob: StoredBlob,
+) -> rusqlite::Result<FileInfo> {
+    let uploaded_at = Utc::now();
+    let path_str = blob.path.to_string_lossy().to_string();
+    let extension_seconds = state.meja_list.get(meja_id).map_or(0, Meja::extension_seconds);
+    let seconds_late = state.timer.seconds_late(blob.received_at, extension_seconds);
+
+    let version = versions::next_version(conn, meja_id, filename)?;
+    conn.execute(
+        "INSERT INTO files (id, meja_id, filename, size, uploaded_at, path, version, sha256, seconds_late) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![blob.file_id, meja_id, filename, blob.size as i64, uploaded_at.to_rfc3339(), path_str, version, blob.sha256, seconds_late],
+    )?;
+
+    Ok(FileInfo {
+        id: blob.file_id,
+        filename: filename.to_string(),
+        size: blob.size,
+        uploaded_at,
+        path: path_str,
+        version,
+        is_final: false,
+        sha256: Some(blob.sha256),
+        late: seconds_late.is_some(),
+        seconds_late,
+    })
+}
+
+// Final submit, deadline, dan pemakaian meja bisa berubah selama body upload
+// masih di-stream, jadi dicek ulang tepat sebelum blob dicatat. Deadline
+// dibandingkan dengan saat byte terakhir diterima. `reserved` adalah ruang
+// yang dipesan sesi upload bertahap lain. Mengembalikan file lama yang akan
+// diganti.
+fn check_commit_allowed(
+    state: &AppState,
+    meja_id: &str,
+    filename: &str,
+    blob: &StoredBlob,
+    replaces: Option<&str>,
+    reserved: MejaUsage,
+) -> Result<Option<FileInfo>, UploadFailure> {
+    let Some(meja) = state.meja_list.get(meja_id) else {
+        return Err(UploadFailure::MejaNotFound);
+    };
+    if meja.submitted_at.is_some() {
+        return Err(UploadFailure::Submitted);
+    }
+    if !state.timer.accepts_upload(blob.received_at, meja.extension_seconds()) {
+        return Err(UploadFailure::TimeExpired);
+    }
+    let old = match replaces {
+        // File lama bisa saja sudah dihapus request lain selama upload berjalan
+        Some(file_id) => match meja.files.iter().find(|f| f.id == file_id) {
+            Some(old) => Some(old.clone()),
+            None => return Err(UploadFailure::ReplacedGone),
+        },
+        None => None,
+    };
+
+    let usage = MejaUsage::of(&meja.files).with_reserved(reserved);
+    let usage = old.as_ref().map_or(usage, |old| usage.without(old));
+    let rules = &state.upload_rules;
+    rules
+        .check_new_file(usage, filename)
+        .and_then(|_| rules.check_size(usage, blob.size))
+        .map_err(UploadFailure::Rule)?;
+    Ok(old)
+}
+
+// Catat blob sebagai file meja di bawah write lock state, sehingga tidak ada
+// final submit atau perubahan lain yang menyela antara pengecekan dan
+// pencatatan. File `replaces` ditarik di bawah lock yang sama, dan sesi
+// upload bertahap `session` dibuang hanya jika berhasil. Jika gagal, blob
+// dihapus.
+async fn commit_upload(
+    shared: &SharedState,
+    meja_id: &str,
+    filename: &str,
+    blob: StoredBlob,
+    replaces: Option<&str>,
+    session: Option<&str>,
+) -> Result<FileInfo, UploadFailure> {
+    let path = blob.path.clone();
+    let mut state = shared.state.write().await;
+    let db = shared.db.lock().await;
+    let reserved = resumable::reserved(&db, meja_id, session);
+    let result = match check_commit_allowed(&state, meja_id, filename, &blob, replaces, reserved) {
+        Ok(old) => record_uploaded_file(&db, &state, meja_id, filename, blob)
+            .map(|file| (file, old))
+            .map_err(UploadFailure::storage),
+        Err(failure) => Err(failure),
+    };
+    if let (Ok(_), Some(session)) = (&result, session) {
+        resumable::delete_session(&db, session);
+    }
+    drop(db);
+
+    match result {
+        Ok((file, old)) => {
+            if let Some(old) = old {
+                let replaced = withdraw_meja_file(shared, &mut state, meja_id, &old, WithdrawAction::Replace, Some(&file.id));
+                if let Err(e) = replaced.await {
+                    eprintln!("Gagal menarik file lama {}: {}", old.id, e);
+                }
+            }
+            if let Some(meja) = state.meja_list.get_mut(meja_id) {
+                meja.files.push(file.clone());
+                meja.last_upload = Some(file.uploaded_at);
+            }
+            Ok(file)
+        }
+        Err(failure) => {
+            drop(state);
+            // Blob tanpa baris di database tidak akan pernah terlihat lagi
+            remove_blob(&path).await;
+            Err(failure)
+        }
+    }
+}
+
+async fn issue_receipt(shared: &SharedState, meja_id: &str, file: &FileInfo) -> Option<Receipt> {
+    let state = shared.state.read().await;
+    let meja = state.meja_list.get(meja_id)?;
+    Receipt::issue(&shared.receipt_key, meja, file)
+}
+
+// Hash file yang sudah ada di disk, dipakai untuk upload bertahap yang
+// potongannya datang di beberapa request
+async fn sha256_file(path: &Path) -> std::io::Result<String> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let (hash, _) = digest::copy_hashed(&mut file, &mut tokio::io::sink()).await?;
+    Ok(hash)
+}
+
+async fn read_head(path: &Path) -> std::io::Result<Vec<u8>> {
+    use tokio::io::AsyncReadExt;
+
+    let mut head = Vec::with_capacity(rules::SNIFF_BYTES);
+    tokio::fs::File::open(path)
+        .await?
+        .take(rules::SNIFF_BYTES as u64)
+        .read_to_end(&mut head)
+        .await?;
+    Ok(head)
+}
+
+// Alasan satu file gagal disimpan
+enum UploadFailure {
+    TooLarge,
+    // Stream multipart putus, sisa request tidak bisa dibaca lagi
+    Interrupted,
+    Storage,
+    // Melanggar aturan upload yang diatur admin
+    Rule(RuleViolation),
+    // Meja ditutup (final submit, waktu habis, atau di-generate ulang)
+    // saat upload akan dicatat
+    Submitted,
+    TimeExpired,
+    MejaNotFound,
+    // File yang akan diganti sudah ditarik request lain
+    ReplacedGone,
+}
+
+impl UploadFailure {
+    fn storage(e: impl std::fmt::Display) -> Self {
+        eprintln!("Gagal menyimpan upload: {}", e);
+        UploadFailure::Storage
+    }
+
+    fn message(&self) -> String {
+        match self {
+            UploadFailure::TooLarge => format!("Ukuran file melebihi batas maksimal {}MB", settings().max_file_size_mb()),
+            UploadFailure::Interrupted => "Upload terputus sebelum file selesai diterima".to_string(),
+            UploadFailure::Storage => "Gagal menyimpan file".to_string(),
+            UploadFailure::Rule(violation) => violation.message(),
+            UploadFailure::Submitted => {
+                "Meja sudah final submit, minta admin membuka kembali untuk mengubah file".to_string()
+            }
+            UploadFailure::TimeExpired => "Waktu telah habis!!".to_string(),
+            UploadFailure::MejaNotFound => "Meja not found".to_string(),
+            UploadFailure::ReplacedGone => "File yang diganti sudah dihapus".to_string(),
+        }
+    }
+
+    fn code(&self) -> &'static str {
+        match self {
+            UploadFailure::TooLarge => "file_too_large",
+            UploadFailure::Interrupted => "upload_interrupted",
+            UploadFailure::Storage => "storage_error",
+            UploadFailure::Rule(violation) => violation.code(),
+            UploadFailure::Submitted => "submitted",
+            UploadFailure::TimeExpired => "time_expired",
+            UploadFailure::MejaNotFound => "meja_not_found",
+            UploadFailure::ReplacedGone => "replaced_gone",
+        }
+    }
+
+    // File yang terlalu besar tidak dibaca sampai habis, jadi field berikutnya juga tidak bisa diproses
+    fn aborts_request(&self) -> bool {
+        matches!(
+            self,
+            UploadFailure::TooLarge
+                | UploadFailure::Interrupted
+                | UploadFailure::Rule(RuleViolation::QuotaExceeded { .. })
+                | UploadFailure::Submitted
+                | UploadFailure::TimeExpired
+                | UploadFailure::MejaNotFound
+                | UploadFailure::ReplacedGone
+        )
+    }
+
+    // `error` dan `code`, ditambah batas yang dilanggar jika ada
+    fn body(&self) -> serde_json::Value {
+        match self {
+            UploadFailure::Rule(violation) => violation.body(),
+            UploadFailure::TooLarge => serde_json::json!({
+                "error": self.message(),
+                "code": self.code(),
+                "max_size_mb": settings().max_file_size_mb()
+            }),
+            // Flag yang sama dengan respons pengecekan di awal request
+            UploadFailure::Submitted => serde_json::json!({"error": self.message(), "code": self.code(), "submitted": true}),
+            UploadFailure::TimeExpired => {
+                serde_json::json!({"error": self.message(), "code": self.code(), "time_expired": true})
+            }
+            _ => serde_json::json!({"error": self.message(), "code": self.code()}),
+        }
+    }
+
+    fn response(&self, files: Vec<serde_json::Value>) -> HttpResponse {
+        let mut body = self.body();
+        body["success"] = false.into();
+        body["files"] = files.into();
+        match self {
+            UploadFailure::TooLarge => HttpResponse::PayloadTooLarge().json(body),
+            UploadFailure::Interrupted => HttpResponse::BadRequest().json(body),
+            UploadFailure::Storage => HttpResponse::InternalServerError().json(body),
+            UploadFailure::Rule(violation) => violation.status().json(body),
+            UploadFailure::Submitted | UploadFailure::TimeExpired => HttpResponse::Forbidden().json(body),
+            UploadFailure::MejaNotFound => HttpResponse::NotFound().json(body),
+            UploadFailure::ReplacedGone => HttpResponse::Conflict().json(body),
+        }
+    }
+}
+
+struct SavedUpload {
+    size: u64,
+    sha256: String,
+    // Awal isi file untuk deteksi tipe
+    head: Vec<u8>,
+}
+
+// Isi field ditulis ke file sementara di folder yang sama lalu di-rename,
+// sehingga blob yang setengah jadi tidak pernah tercatat sebagai upload
+async fn save_upload_field(
+    field: &mut actix_multipart::Field,
+    filepath: &Path,
+    max_size: u64,
+) -> Result<SavedUpload, UploadFailure> {
+    let temp_path = filepath.with_file_name(format!(".{}.part", Uuid::new_v4()));
+    let result = match write_upload_field(field, &temp_path, max_size).await {
+        Ok(saved) => tokio::fs::rename(&temp_path, filepath)
+            .await
+            .map(|_| saved)
+            .map_err(UploadFailure::storage),
+        Err(e) => Err(e),
+    };
+    if let Err(failure) = &result {
+        tokio::fs::remove_file(&temp_path).await.ok();
+        // Buang sisa field supaya file berikutnya di request yang sama tetap bisa dibaca
+        if !failure.aborts_request() {
+            while let Some(Ok(_)) = field.next().await {}
+        }
+    }
+    result
+}
+
+async fn write_upload_field(
+    field: &mut actix_multipart::Field,
+    temp_path: &Path,
+    max_size: u64,
+) -> Result<SavedUpload, UploadFailure> {
+    use sha2::{Digest, Sha256};
+
+    // Async file I/O dengan buffer besar untuk kecepatan maksimal
+    let file = tokio::fs::File::create(temp_path).await.map_err(UploadFailure::storage)?;
+    let mut file = tokio::io::BufWriter::with_capacity(512 * 1024, file); // 512KB buffer
+
+    let mut hasher = Sha256::new();
+    let mut size: u64 = 0;
+    let mut head = Vec::with_capacity(rules::SNIFF_BYTES);
+    let mut buffer = Vec::with_capacity(2 * 1024 * 1024); // 2MB pre-allocated buffer
+
+    while let Some(chunk) = field.next().await {
+        let data = chunk.map_err(|_| UploadFailure::Interrupted)?;
+        size += data.len() as u64;
+        if size > max_size {
+            return Err(UploadFailure::TooLarge);
+        }
+        hasher.update(&data);
+        if head.len() < rules::SNIFF_BYTES {
+            let take = (rules::SNIFF_BYTES - head.len()).min(data.len());
+            head.extend_from_slice(&data[..take]);
+        }
+        buffer.extend_from_slice(&data);
+
+        // Flush ke disk setiap 4MB untuk balance memory dan I/O
+        if buffer.len() >= 4 * 1024 * 1024 {
+            file.write_all(&buffer).await.map_err(UploadFailure::storage)?;
+            buffer.clear();
         }
     }
 
-    let upload_path = get_uploads_path(&meja_id);
+    file.write_all(&buffer).await.map_err(UploadFailure::storage)?;
+    file.flush().await.map_err(UploadFailure::storage)?;
+    // Isi harus sudah di disk sebelum rename menggantikan file lama
+    file.get_ref().sync_all().await.map_err(UploadFailure::storage)?;
+
+    Ok(SavedUpload {
+        size,
+        sha256: digest::hex(&hasher.finalize()),
+        head,
+    })
+}
+
+// Aturan upload dan pemakaian meja saat ini
+fn upload_rules_for(state: &AppState, meja_id: &str) -> (UploadRules, MejaUsage) {
+    let usage = state
+        .meja_list
+        .get(meja_id)
+        .map(|meja| MejaUsage::of(&meja.files))
+        .unwrap_or_default();
+    (state.upload_rules.clone(), usage)
+}
+
+// Simpan satu field multipart sebagai upload baru setelah lolos aturan upload
+async fn store_upload_field(
+    shared: &SharedState,
+    meja_id: &str,
+    field: &mut actix_multipart::Field,
+    filename: &str,
+    rules: &UploadRules,
+    usage: MejaUsage,
+    replaces: Option<&str>,
+) -> Result<FileInfo, UploadFailure> {
+    if let Err(violation) = rules.check_new_file(usage, filename) {
+        let failure = UploadFailure::Rule(violation);
+        if !failure.aborts_request() {
+            while let Some(Ok(_)) = field.next().await {}
+        }
+        return Err(failure);
+    }
+
+    let file_id = Uuid::new_v4().to_string();
+    let filepath = upload_blob_path(meja_id, &file_id, filename);
+    let (max_size, quota) = rules.size_limit(usage, settings().max_file_size);
+    let saved = match save_upload_field(field, &filepath, max_size).await {
+        Ok(saved) => saved,
+        Err(failure) => {
+            remove_blob(&filepath).await;
+            return Err(match (failure, quota) {
+                (UploadFailure::TooLarge, Some(violation)) => UploadFailure::Rule(violation),
+                (failure, _) => failure,
+            })
+        }
+    };
+    if let Err(violation) = rules.check_content(filename, &saved.head) {
+        remove_blob(&filepath).await;
+        return Err(UploadFailure::Rule(violation));
+    }
+
+    let blob = StoredBlob {
+        file_id,
+        path: filepath,
+        size: saved.size,
+        sha256: saved.sha256,
+        received_at: Utc::now(),
+    };
+    commit_upload(shared, meja_id, filename, blob, replaces, None).await
+}
+
+// Respons berisi hasil per file; status error hanya dipakai jika tidak ada
+// satu pun file yang tersimpan
+async fn upload_file(
+    req: HttpRequest,
+    shared: web::Data<Arc<SharedState>>,
+    path: web::Path<String>,
+    mut payload: Multipart,
+) -> impl Responder {
+    let meja_id = path.into_inner();
+    if !verify_peserta_token(&req, &meja_id) {
+        return HttpResponse::Unauthorized().json(serde_json::json!({"error": "Unauthorized"}));
+    }
+
+    let (rules, mut usage) = {
+        let state = shared.state.read().await;
+        if let Err(resp) = check_upload_allowed(&state, &meja_id) {
+            return resp;
+        }
+        upload_rules_for(&state, &meja_id)
+    };
+
     let mut uploaded_files = vec![];
+    let mut results = vec![];
+    let mut first_failure = None;
 
     while let Ok(Some(mut field)) = payload.try_next().await {
         let content_disposition = field.content_disposition();
         let filename = content_disposition
-            .and_then(|cd| cd.get_filename().map(|f| sanitize_filename::sanitize(f)))
+            .and_then(|cd| cd.get_filename().map(sanitize_filename::sanitize))
             .unwrap_or_else(|| Uuid::new_v4().to_string());
 
-        let filepath = upload_path.join(&filename);
-        
-        // Async file I/O dengan buffer besar untuk kecepatan maksimal
-        let mut file = match tokio::fs::File::create(&filepath).await {
-            Ok(f) => tokio::io::BufWriter::with_capacity(512 * 1024, f), // 512KB buffer
-            Err(_) => continue,
-        };
-
-        let mut size: u64 = 0;
-        let mut size_exceeded = false;
-        let mut buffer = Vec::with_capacity(2 * 1024 * 1024); // 2MB pre-allocated buffer
-
-        while let Some(chunk) = field.next().await {
-            if let Ok(data) = chunk {
-                size += data.len() as u64;
-                if size > MAX_FILE_SIZE {
-                    size_exceeded = true;
+        match store_upload_field(&shared, &meja_id, &mut field, &filename, &rules, usage, None).await {
+            Ok(file) => {
+                usage.add(&file);
+                results.push(serde_json::json!({
+                    "filename": filename,
+                    "success": true,
+                    "file": file,
+                    "receipt": issue_receipt(&shared, &meja_id, &file).await
+                }));
+                uploaded_files.push(file);
+            }
+            Err(failure) => {
+                let mut result = failure.body();
+                result["filename"] = filename.into();
+                result["success"] = false.into();
+                results.push(result);
+                let stop = failure.aborts_request();
+                first_failure.get_or_insert(failure);
+                if stop {
                     break;
                 }
-                buffer.extend_from_slice(&data);
-                
-                // Flush ke disk setiap 4MB untuk balance memory dan I/O
-                if buffer.len() >= 4 * 1024 * 1024 {
-                    if file.write_all(&buffer).await.is_err() {
-                        break;
-                    }
-                    buffer.clear();
-                }
             }
         }
+    }
+
+    if uploaded_files.is_empty() {
+        if let Some(failure) = first_failure {
+            return failure.response(results);
+        }
+    } else {
+        broadcast_state(&shared).await;
+    }
+
+    let failed = results.iter().filter(|r| r["success"] == false).count();
+    if failed > 0 {
+        return HttpResponse::Ok().json(serde_json::json!({
+            "success": false,
+            "error": format!("{} file gagal diupload", failed),
+            "files": results
+        }));
+    }
+    HttpResponse::Ok().json(serde_json::json!({"success": true, "files": results}))
+}
+
+// Catat file ke audit, hapus dari state, lalu hapus blob-nya. Blob hanya
+// dihapus jika tidak dipakai versi lain (upload lama bisa berbagi satu blob).
+async fn withdraw_meja_file(
+    shared: &SharedState,
+    state: &mut AppState,
+    meja_id: &str,
+    file: &FileInfo,
+    action: WithdrawAction,
+    replaced_by: Option<&str>,
+) -> rusqlite::Result<()> {
+    audit::withdraw(&*shared.db.lock().await, meja_id, file, action, replaced_by)?;
+
+    let Some(meja) = state.meja_list.get_mut(meja_id) else {
+        return Ok(());
+    };
+    meja.files.retain(|f| f.id != file.id);
+    if !meja.files.iter().any(|f| f.path == file.path) {
+        if let Some(filepath) = resolve_storage_path(&file.path) {
+            remove_blob(&filepath).await;
+        }
+    }
+    Ok(())
+}
+
+async fn delete_meja_file(
+    req: HttpRequest,
+    shared: web::Data<Arc<SharedState>>,
+    path: web::Path<(String, String)>,
+) -> HttpResponse {
+    let (meja_id, file_id) = path.into_inner();
+    if !verify_peserta_token(&req, &meja_id) {
+        return HttpResponse::Unauthorized().json(serde_json::json!({"error": "Unauthorized"}));
+    }
+
+    let mut state = shared.state.write().await;
+    if let Err(resp) = check_upload_allowed(&state, &meja_id) {
+        return resp;
+    }
+    let Some(file) = find_meja_file(&state, &meja_id, &file_id).cloned() else {
+        return HttpResponse::NotFound().json(serde_json::json!({"error": "File not found"}));
+    };
+
+    if let Err(e) = withdraw_meja_file(&shared, &mut state, &meja_id, &file, WithdrawAction::Delete, None).await {
+        eprintln!("Gagal menghapus file {}: {}", file_id, e);
+        return HttpResponse::InternalServerError().json(serde_json::json!({"error": "Gagal menghapus file"}));
+    }
+    drop(state);
+    broadcast_state(&shared).await;
+
+    HttpResponse::Ok().json(serde_json::json!({"success": true}))
+}
+
+// Ganti satu file dengan file pertama di multipart. File baru dicatat sebagai
+// upload biasa (versi mengikuti namanya), file lama masuk audit sebagai `replace`.
+async fn replace_meja_file(
+    req: HttpRequest,
+    shared: web::Data<Arc<SharedState>>,
+    path: web::Path<(String, String)>,
+    mut payload: Multipart,
+) -> HttpResponse {
+    let (meja_id, file_id) = path.into_inner();
+    if !verify_peserta_token(&req, &meja_id) {
+        return HttpResponse::Unauthorized().json(serde_json::json!({"error": "Unauthorized"}));
+    }
+
+    // File lama tidak dihitung ke kuota karena akan ditarik
+    let (rules, usage) = {
+        let state = shared.state.read().await;
+        if let Err(resp) = check_upload_allowed(&state, &meja_id) {
+            return resp;
+        }
+        let Some(old) = find_meja_file(&state, &meja_id, &file_id) else {
+            return HttpResponse::NotFound().json(serde_json::json!({"error": "File not found"}));
+        };
+        let (rules, usage) = upload_rules_for(&state, &meja_id);
+        (rules, usage.without(old))
+    };
+
+    let Ok(Some(mut field)) = payload.try_next().await else {
+        return HttpResponse::BadRequest().json(serde_json::json!({"error": "Tidak ada file yang dikirim"}));
+    };
+    let filename = field
+        .content_disposition()
+        .and_then(|cd| cd.get_filename().map(sanitize_filename::sanitize))
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    let file = match store_upload_field(&shared, &meja_id, &mut field, &filename, &rules, usage, Some(&file_id)).await {
+        Ok(file) => file,
+        Err(failure) => return failure.response(vec![]),
+    };
+    broadcast_state(&shared).await;
+
+    let receipt = issue_receipt(&shared, &meja_id, &file).await;
+    HttpResponse::Ok().json(serde_json::json!({"success": true, "file": file, "receipt": receipt}))
+}
+
+// === Upload Bertahap ===
+
+fn upload_not_found() -> HttpResponse {
+    HttpResponse::NotFound().json(serde_json::json!({"error": "Sesi upload tidak ditemukan"}))
+}
+
+fn upload_busy() -> HttpResponse {
+    HttpResponse::Conflict().json(serde_json::json!({"error": "Upload sedang diproses request lain"}))
+}
+
+async fn find_upload_session(shared: &SharedState, meja_id: &str, upload_id: &str) -> Option<resumable::UploadSession> {
+    let db = shared.db.lock().await;
+    resumable::get_session(&db, upload_id).filter(|s| s.meja_id == meja_id)
+}
+
+async fn init_upload(
+    req: HttpRequest,
+    shared: web::Data<Arc<SharedState>>,
+    path: web::Path<String>,
+    body: web::Json<InitUploadRequest>,
+) -> impl Responder {
+    let meja_id = path.into_inner();
+    if !verify_peserta_token(&req, &meja_id) {
+        return HttpResponse::Unauthorized().json(serde_json::json!({"error": "Unauthorized"}));
+    }
+    if body.size > settings().max_file_size {
+        return file_too_large_response();
+    }
+
+    let filename = sanitize_filename::sanitize(&body.filename);
+    if filename.is_empty() {
+        return HttpResponse::BadRequest().json(serde_json::json!({"error": "Nama file tidak valid"}));
+    }
+
+    // Ukuran sudah diketahui di awal, jadi kuota bisa dicek sebelum data dikirim.
+    // Isi file baru dicek saat finalize. Sesi lain yang masih terbuka ikut
+    // memakan kuota karena ruangnya sudah dipesan.
+    let state = shared.state.read().await;
+    if let Err(resp) = check_upload_allowed(&state, &meja_id) {
+        return resp;
+    }
+    let db = shared.db.lock().await;
+    resumable::sweep_expired(&db, &shared.upload_locks);
+    let reserved = resumable::reserved(&db, &meja_id, None);
+    if reserved.files >= resumable::MAX_SESSIONS_PER_MEJA {
+        return HttpResponse::Conflict().json(serde_json::json!({
+            "error": "Terlalu banyak upload yang belum selesai, selesaikan atau batalkan upload lain terlebih dahulu",
+            "max_sessions": resumable::MAX_SESSIONS_PER_MEJA
+        }));
+    }
+    let (rules, usage) = upload_rules_for(&state, &meja_id);
+    let usage = usage.with_reserved(reserved);
+    if let Err(violation) = rules
+        .check_new_file(usage, &filename)
+        .and_then(|_| rules.check_size(usage, body.size))
+    {
+        return violation.response();
+    }
+
+    let session = match resumable::create_session(&db, &meja_id, &filename, body.size) {
+        Ok(session) => session,
+        Err(e) => {
+            eprintln!("Gagal membuat sesi upload: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({"error": "Gagal membuat sesi upload"}));
+        }
+    };
+    drop(db);
+    drop(state);
+
+    if let Err(e) = tokio::fs::File::create(resumable::partial_path(&session.id)).await {
+        eprintln!("Gagal membuat file upload {}: {}", session.id, e);
+        resumable::delete_session(&*shared.db.lock().await, &session.id);
+        return HttpResponse::InternalServerError().json(serde_json::json!({"error": "Gagal membuat sesi upload"}));
+    }
+
+    HttpResponse::Ok().json(session)
+}
+
+// Klien memanggil ini setelah koneksi putus untuk tahu harus lanjut dari offset mana
+async fn get_upload(
+    req: HttpRequest,
+    shared: web::Data<Arc<SharedState>>,
+    path: web::Path<(String, String)>,
+) -> impl Responder {
+    let (meja_id, upload_id) = path.into_inner();
+    if !verify_peserta_token(&req, &meja_id) {
+        return HttpResponse::Unauthorized().json(serde_json::json!({"error": "Unauthorized"}));
+    }
+    match find_upload_session(&shared, &meja_id, &upload_id).await {
+        Some(session) => HttpResponse::Ok().json(session),
+        None => upload_not_found(),
+    }
+}
+
+// Body berisi byte mentah mulai dari header `Upload-Offset`, yang harus sama
+// dengan offset di server. Byte yang sempat diterima tetap dihitung walaupun
+// koneksi putus di tengah request.
+async fn upload_chunk(
+    req: HttpRequest,
+    shared: web::Data<Arc<SharedState>>,
+    path: web::Path<(String, String)>,
+    mut payload: web::Payload,
+) -> impl Responder {
+    use tokio::io::AsyncSeekExt;
+
+    let (meja_id, upload_id) = path.into_inner();
+    if !verify_peserta_token(&req, &meja_id) {
+        return HttpResponse::Unauthorized().json(serde_json::json!({"error": "Unauthorized"}));
+    }
+    if let Err(resp) = check_upload_allowed(&*shared.state.read().await, &meja_id) {
+        return resp;
+    }
+    let Some(_lock) = shared.upload_locks.acquire(&upload_id) else {
+        return upload_busy();
+    };
+    let Some(session) = find_upload_session(&shared, &meja_id, &upload_id).await else {
+        return upload_not_found();
+    };
+
+    let offset = req
+        .headers()
+        .get("Upload-Offset")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    if offset != Some(session.offset) {
+        return HttpResponse::Conflict().json(serde_json::json!({
+            "error": "Offset tidak sesuai",
+            "offset": session.offset
+        }));
+    }
+
+    let partial = resumable::partial_path(&upload_id);
+    let file = tokio::fs::OpenOptions::new().write(true).create(true).truncate(false).open(&partial).await;
+    let mut file = match file {
+        Ok(f) => f,
+        Err(e) => {
+            eprintln!("Gagal membuka file upload {}: {}", upload_id, e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({"error": "Gagal menyimpan file"}));
+        }
+    };
+    let too_large = |offset: u64| {
+        HttpResponse::PayloadTooLarge().json(serde_json::json!({
+            "error": "Data melebihi ukuran file yang didaftarkan",
+            "offset": offset
+        }))
+    };
+    let content_length = req
+        .headers()
+        .get(actix_web::http::header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    if content_length.is_some_and(|len| session.offset + len > session.size) {
+        return too_large(session.offset);
+    }
+
+    // Sisa byte dari request sebelumnya yang offset-nya tidak sempat dicatat dibuang dulu
+    if file.set_len(session.offset).await.is_err() || file.seek(std::io::SeekFrom::Start(session.offset)).await.is_err() {
+        return HttpResponse::InternalServerError().json(serde_json::json!({"error": "Gagal menyimpan file"}));
+    }
+
+    let mut written: u64 = 0;
+    let mut size_exceeded = false;
+    while let Some(chunk) = payload.next().await {
+        let Ok(data) = chunk else {
+            break;
+        };
+        if session.offset + written + data.len() as u64 > session.size {
+            size_exceeded = true;
+            break;
+        }
+        if file.write_all(&data).await.is_err() {
+            break;
+        }
+        written += data.len() as u64;
+    }
+    let new_offset = if file.flush().await.is_ok() {
+        session.offset + written
+    } else {
+        session.offset
+    };
+
+    resumable::set_offset(&*shared.db.lock().await, &upload_id, new_offset);
+
+    if size_exceeded {
+        return too_large(new_offset);
+    }
+
+    HttpResponse::Ok().json(serde_json::json!({
+        "offset": new_offset,
+        "size": session.size
+    }))
+}
+
+// Timer dicek terhadap waktu potongan terakhir diterima, jadi finalize yang
+// sedikit terlambat tetap diterima selama semua byte masuk sebelum deadline.
+async fn finalize_upload(
+    req: HttpRequest,
+    shared: web::Data<Arc<SharedState>>,
+    path: web::Path<(String, String)>,
+) -> impl Responder {
+    let (meja_id, upload_id) = path.into_inner();
+    if !verify_peserta_token(&req, &meja_id) {
+        return HttpResponse::Unauthorized().json(serde_json::json!({"error": "Unauthorized"}));
+    }
+    let Some(_lock) = shared.upload_locks.acquire(&upload_id) else {
+        return upload_busy();
+    };
+    let Some(session) = find_upload_session(&shared, &meja_id, &upload_id).await else {
+        return upload_not_found();
+    };
+    if session.offset != session.size {
+        return HttpResponse::Conflict().json(serde_json::json!({
+            "error": "Upload belum lengkap",
+            "offset": session.offset
+        }));
+    }
+    // Dicek ulang karena meja bisa saja sudah menerima upload lain sejak sesi dibuat
+    let rules = {
+        let state = shared.state.read().await;
+        if let Err(resp) = check_meja_open(&state, &meja_id) {
+            return resp;
+        }
+        let (rules, usage) = upload_rules_for(&state, &meja_id);
+        if let Err(violation) = rules
+            .check_new_file(usage, &session.filename)
+            .and_then(|_| rules.check_size(usage, session.size))
+        {
+            return violation.response();
+        }
+        rules
+    };
+
+    let partial = resumable::partial_path(&upload_id);
+    let head = match read_head(&partial).await {
+        Ok(head) => head,
+        Err(e) => {
+            eprintln!("Gagal membaca upload {}: {}", upload_id, e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({"error": "Gagal menyimpan file"}));
+        }
+    };
+    // Isi file tidak akan berubah, jadi sesinya langsung dibuang
+    if let Err(violation) = rules.check_content(&session.filename, &head) {
+        resumable::delete_session(&*shared.db.lock().await, &upload_id);
+        return violation.response();
+    }
+    let sha256 = match sha256_file(&partial).await {
+        Ok(sha256) => sha256,
+        Err(e) => {
+            eprintln!("Gagal membaca upload {}: {}", upload_id, e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({"error": "Gagal menyimpan file"}));
+        }
+    };
+
+    // Keterlambatan dihitung dari potongan terakhir yang diterima, bukan waktu finalize
+    let received_at = tokio::fs::metadata(&partial)
+        .await
+        .and_then(|meta| meta.modified())
+        .map(DateTime::<Utc>::from)
+        .unwrap_or_else(|_| Utc::now());
+
+    let file_id = Uuid::new_v4().to_string();
+    let filepath = upload_blob_path(&meja_id, &file_id, &session.filename);
+    if let Err(e) = tokio::fs::rename(&partial, &filepath).await {
+        remove_blob(&filepath).await;
+        eprintln!("Gagal memindahkan upload {}: {}", upload_id, e);
+        return HttpResponse::InternalServerError().json(serde_json::json!({"error": "Gagal menyimpan file"}));
+    }
+
+    let blob = StoredBlob {
+        file_id,
+        path: filepath,
+        size: session.size,
+        sha256,
+        received_at,
+    };
+    let file = match commit_upload(&shared, &meja_id, &session.filename, blob, None, Some(&upload_id)).await {
+        Ok(file) => file,
+        Err(failure) => {
+            // Byte yang diterima sudah ikut terhapus bersama blob
+            resumable::delete_session(&*shared.db.lock().await, &upload_id);
+            return failure.response(Vec::new());
+        }
+    };
+    broadcast_state(&shared).await;
+
+    let receipt = issue_receipt(&shared, &meja_id, &file).await;
+    HttpResponse::Ok().json(serde_json::json!({"success": true, "file": file, "receipt": receipt}))
+}
+
+async fn cancel_upload(
+    req: HttpRequest,
+    shared: web::Data<Arc<SharedState>>,
+    path: web::Path<(String, String)>,
+) -> impl Responder {
+    let (meja_id, upload_id) = path.into_inner();
+    if !verify_peserta_token(&req, &meja_id) {
+        return HttpResponse::Unauthorized().json(serde_json::json!({"error": "Unauthorized"}));
+    }
+    let Some(_lock) = shared.upload_locks.acquire(&upload_id) else {
+        return upload_busy();
+    };
+    if find_upload_session(&shared, &meja_id, &upload_id).await.is_none() {
+        return upload_not_found();
+    }
+    resumable::delete_session(&*shared.db.lock().await, &upload_id);
+    HttpResponse::Ok().json(serde_json::json!({"success": true}))
+}
+
+async fn get_meja(
+    req: HttpRequest,
+    shared: web::Data<Arc<SharedState>>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let meja_id = path.into_inner();
+    if !verify_peserta_token(&req, &meja_id) {
+        return HttpResponse::Unauthorized().json(serde_json::json!({"error": "Unauthorized"}));
+    }
+    let state = shared.state.read().await;
+
+    if let Some(meja) = state.meja_list.get(&meja_id) {
+        return HttpResponse::Ok().json(meja);
+    }
 
-        // Write remaining buffer
-        if !buffer.is_empty() && !size_exceeded {
-            file.write_all(&buffer).await.ok();
-        }
-        file.flush().await.ok();
+    HttpResponse::NotFound().json(serde_json::json!({"error": "Meja not found"}))
+}
 
-        if size_exceeded {
-            tokio::fs::remove_file(&filepath).await.ok();
-            return HttpResponse::PayloadTooLarge().json(serde_json::json!({
-                "error": "Ukuran file melebihi batas maksimal 300MB",
-                "max_size_mb": 300
-            }));
-        }
+async fn get_soal_list(shared: web::Data<Arc<SharedState>>) -> impl Responder {
+    let state = shared.state.read().await;
+    HttpResponse::Ok().json(&state.soal_files)
+}
 
-        let file_id = Uuid::new_v4().to_string();
-        let uploaded_at = Utc::now();
-        let path_str = filepath.to_string_lossy().to_string();
+// Download file peserta: admin semua meja, peserta hanya mejanya sendiri.
+// NamedFile menangani Range request dan ETag.
+async fn download_meja_file(
+    req: HttpRequest,
+    shared: web::Data<Arc<SharedState>>,
+    path: web::Path<(String, String)>,
+) -> HttpResponse {
+    let (meja_id, file_id) = path.into_inner();
 
-        let db = shared.db.lock().await;
-        db.execute(
-            "INSERT INTO files (id, meja_id, filename, size, uploaded_at, path) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-            params![file_id, meja_id, filename, size as i64, uploaded_at.to_rfc3339(), path_str],
-        ).ok();
-        drop(db);
+    if !can_read_meja_files(&req, &meja_id) {
+        return HttpResponse::Unauthorized().json(serde_json::json!({"error": "Unauthorized"}));
+    }
 
-        uploaded_files.push(FileInfo {
-            id: file_id,
-            filename: filename.clone(),
-            size,
-            uploaded_at,
-            path: path_str,
-        });
+    let (filepath, filename) = {
+        let state = shared.state.read().await;
+        let Some(file) = find_meja_file(&state, &meja_id, &file_id) else {
+            return HttpResponse::NotFound().json(serde_json::json!({"error": "File not found"}));
+        };
+        (resolve_storage_path(&file.path), file.filename.clone())
+    };
+    let Some(filepath) = filepath else {
+        return HttpResponse::NotFound().json(serde_json::json!({"error": "File not found"}));
+    };
+
+    match NamedFile::open_async(&filepath).await {
+        Ok(file) => file
+            .set_content_disposition(ContentDisposition {
+                disposition: DispositionType::Attachment,
+                parameters: vec![DispositionParam::Filename(filename)],
+            })
+            .into_response(&req),
+        Err(_) => HttpResponse::NotFound().json(serde_json::json!({"error": "File not found"})),
+    }
+}
+
+// Peserta memilih versi yang dinilai; versi lain dengan nama sama dilepas tandanya
+async fn mark_file_final(
+    req: HttpRequest,
+    shared: web::Data<Arc<SharedState>>,
+    path: web::Path<(String, String)>,
+) -> HttpResponse {
+    let (meja_id, file_id) = path.into_inner();
+    if !verify_peserta_token(&req, &meja_id) {
+        return HttpResponse::Unauthorized().json(serde_json::json!({"error": "Unauthorized"}));
     }
 
     let mut state = shared.state.write().await;
-    if let Some(meja) = state.meja_list.get_mut(&meja_id) {
-        meja.files.extend(uploaded_files);
-        meja.last_upload = Some(Utc::now());
+    if let Err(resp) = check_upload_allowed(&state, &meja_id) {
+        return resp;
     }
+    let Some(file) = find_meja_file(&state, &meja_id, &file_id).cloned() else {
+        return HttpResponse::NotFound().json(serde_json::json!({"error": "File not found"}));
+    };
 
+    if let Err(e) = versions::mark_final(&*shared.db.lock().await, &meja_id, &file) {
+        eprintln!("Gagal menandai versi final {}: {}", file_id, e);
+        return HttpResponse::InternalServerError().json(serde_json::json!({"error": "Gagal menyimpan versi final"}));
+    }
+    if let Some(meja) = state.meja_list.get_mut(&meja_id) {
+        for f in meja.files.iter_mut().filter(|f| f.filename == file.filename) {
+            f.is_final = f.id == file.id;
+        }
+    }
     drop(state);
     broadcast_state(&shared).await;
 
     HttpResponse::Ok().json(serde_json::json!({"success": true}))
 }
 
-async fn get_meja(
+#[derive(Deserialize)]
+pub struct DiffQuery {
+    // Default: versi sebelumnya dari file yang sama
+    pub against: Option<String>,
+}
+
+async fn diff_meja_file(
+    req: HttpRequest,
     shared: web::Data<Arc<SharedState>>,
-    path: web::Path<String>,
-) -> impl Responder {
-    let meja_id = path.into_inner();
-    let state = shared.state.read().await;
+    path: web::Path<(String, String)>,
+    query: web::Query<DiffQuery>,
+) -> HttpResponse {
+    if let Err(resp) = authorize_admin(&req, Permission::ReadSubmissions) {
+        return resp;
+    }
+    let (meja_id, file_id) = path.into_inner();
 
-    if let Some(meja) = state.meja_list.get(&meja_id) {
-        return HttpResponse::Ok().json(meja);
+    let (old, new) = {
+        let state = shared.state.read().await;
+        let Some(meja) = state.meja_list.get(&meja_id) else {
+            return HttpResponse::NotFound().json(serde_json::json!({"error": "Meja not found"}));
+        };
+        let Some(new) = meja.files.iter().find(|f| f.id == file_id) else {
+            return HttpResponse::NotFound().json(serde_json::json!({"error": "File not found"}));
+        };
+        let old = match &query.against {
+            Some(against) => meja.files.iter().find(|f| &f.id == against),
+            None => versions::previous(&meja.files, new),
+        };
+        let Some(old) = old else {
+            return HttpResponse::NotFound().json(serde_json::json!({"error": "Versi pembanding tidak ditemukan"}));
+        };
+        (old.clone(), new.clone())
+    };
+
+    let mut texts = vec![];
+    for file in [&old, &new] {
+        let Some(filepath) = resolve_storage_path(&file.path) else {
+            return HttpResponse::NotFound().json(serde_json::json!({"error": "File not found"}));
+        };
+        if file.size > TEXT_PREVIEW_LIMIT {
+            return HttpResponse::PayloadTooLarge().json(serde_json::json!({"error": "File terlalu besar untuk dibandingkan"}));
+        }
+        let text = match tokio::fs::read(&filepath).await.map(String::from_utf8) {
+            Ok(Ok(text)) => text,
+            Ok(Err(_)) => {
+                return HttpResponse::UnprocessableEntity()
+                    .json(serde_json::json!({"error": "File biner tidak bisa dibandingkan"}))
+            }
+            Err(_) => return HttpResponse::NotFound().json(serde_json::json!({"error": "File not found"})),
+        };
+        texts.push(text);
     }
 
-    HttpResponse::NotFound().json(serde_json::json!({"error": "Meja not found"}))
+    let label = |f: &FileInfo| format!("{} (v{})", f.filename, f.version);
+    let diff = versions::unified_diff(&texts[0], &texts[1], &label(&old), &label(&new));
+    HttpResponse::Ok().json(serde_json::json!({
+        "from": {"id": old.id, "filename": old.filename, "version": old.version},
+        "to": {"id": new.id, "filename": new.filename, "version": new.version},
+        "identical": texts[0] == texts[1],
+        "diff": diff
+    }))
 }
 
-async fn get_soal_list(shared: web::Data<Arc<SharedState>>) -> impl Responder {
+// Bukti upload bisa diunduh ulang kapan saja oleh peserta pemilik meja
+async fn get_file_receipt(
+    req: HttpRequest,
+    shared: web::Data<Arc<SharedState>>,
+    path: web::Path<(String, String)>,
+) -> HttpResponse {
+    let (meja_id, file_id) = path.into_inner();
+    if !can_read_meja_files(&req, &meja_id) {
+        return HttpResponse::Unauthorized().json(serde_json::json!({"error": "Unauthorized"}));
+    }
+
     let state = shared.state.read().await;
-    HttpResponse::Ok().json(&state.soal_files)
+    let (Some(meja), Some(file)) = (state.meja_list.get(&meja_id), find_meja_file(&state, &meja_id, &file_id)) else {
+        return HttpResponse::NotFound().json(serde_json::json!({"error": "File not found"}));
+    };
+    match Receipt::issue(&shared.receipt_key, meja, file) {
+        Some(receipt) => HttpResponse::Ok().json(receipt),
+        None => HttpResponse::NotFound().json(serde_json::json!({"error": "File ini diupload sebelum ada bukti upload"})),
+    }
+}
+
+// Cek bukti yang ditunjukkan peserta: tanda tangan harus valid dan isinya
+// harus sama dengan file yang tercatat di server
+async fn verify_receipt(
+    req: HttpRequest,
+    shared: web::Data<Arc<SharedState>>,
+    body: web::Json<Receipt>,
+) -> HttpResponse {
+    if let Err(resp) = authorize_admin(&req, Permission::ReadSubmissions) {
+        return resp;
+    }
+
+    let receipt = body.into_inner();
+    if !receipt.verify_signature(&shared.receipt_key) {
+        return HttpResponse::Ok().json(serde_json::json!({
+            "valid": false,
+            "signature_valid": false,
+            "message": "Tanda tangan tidak valid, bukti ini tidak diterbitkan server atau sudah diubah"
+        }));
+    }
+
+    let state = shared.state.read().await;
+    let recorded = state
+        .meja_list
+        .get(&receipt.meja_id)
+        .and_then(|meja| Some((meja, find_meja_file(&state, &meja.id, &receipt.file_id)?)));
+    let (valid, message, file) = match recorded {
+        Some((meja, file)) if receipt.matches(meja, file) => (true, "Bukti valid dan sesuai dengan file di server", Some(file)),
+        Some((_, file)) => (false, "Bukti asli, tetapi tidak sesuai dengan catatan file di server", Some(file)),
+        None => (false, "Bukti asli, tetapi file sudah tidak ada di server", None),
+    };
+    HttpResponse::Ok().json(serde_json::json!({
+        "valid": valid,
+        "signature_valid": true,
+        "message": message,
+        "file": file
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct WithdrawnFilesQuery {
+    pub meja_id: Option<String>,
+}
+
+async fn list_withdrawn_files(
+    req: HttpRequest,
+    shared: web::Data<Arc<SharedState>>,
+    query: web::Query<WithdrawnFilesQuery>,
+) -> HttpResponse {
+    if let Err(resp) = authorize_admin(&req, Permission::ReadSubmissions) {
+        return resp;
+    }
+
+    match audit::list(&*shared.db.lock().await, query.meja_id.as_deref()) {
+        Ok(entries) => HttpResponse::Ok().json(entries),
+        Err(e) => {
+            eprintln!("Gagal membaca audit file: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({"error": "Gagal membaca audit file"}))
+        }
+    }
 }
 
 async fn download_soal(
@@ -1039,112 +2971,395 @@ async fn download_soal(
 
 // === Archive Preview ===
 
-async fn preview_archive(path: web::Path<(String, String)>) -> impl Responder {
-    let (meja_id, _file_id) = path.into_inner();
-    let upload_path = get_uploads_path(&meja_id);
-
-    let entries: Vec<ArchiveEntry> = vec![];
-
-    if let Ok(mut dir) = tokio::fs::read_dir(&upload_path).await {
-        while let Ok(Some(entry)) = dir.next_entry().await {
-            let filepath = entry.path();
-            let filename = filepath.file_name().unwrap_or_default().to_string_lossy();
-
-            if filename.to_lowercase().ends_with(".zip") {
-                if let Ok(file) = std::fs::File::open(&filepath) {
-                    if let Ok(mut archive) = zip::ZipArchive::new(file) {
-                        let mut entries = vec![];
-                        for i in 0..archive.len() {
-                            if let Ok(file) = archive.by_index(i) {
-                                entries.push(ArchiveEntry {
-                                    name: file.name().to_string(),
-                                    size: file.size(),
-                                    is_dir: file.is_dir(),
-                                });
-                            }
-                        }
-                        return HttpResponse::Ok().json(ArchiveContent { files: entries });
-                    }
-                }
+// Path dari AppState dikanonikalisasi dan harus berada di dalam folder storage
+fn resolve_storage_path(path: &str) -> Option<PathBuf> {
+    let root = get_storage_path().canonicalize().ok()?;
+    let resolved = PathBuf::from(path).canonicalize().ok()?;
+    if resolved.starts_with(&root) && resolved.is_file() {
+        Some(resolved)
+    } else {
+        None
+    }
+}
+
+// File meja boleh dibuka admin (semua role) atau peserta pemilik meja
+fn authorize_meja_access(req: &HttpRequest, meja_id: &str) -> Result<(), HttpResponse> {
+    if verify_peserta_token(req, meja_id) {
+        return Ok(());
+    }
+    authorize_admin(req, Permission::ReadSubmissions).map(|_| ())
+}
+
+// Seperti authorize_meja_access, tapi token boleh lewat `?token=` agar
+// bisa dipakai langsung sebagai link download di browser
+fn can_read_meja_files(req: &HttpRequest, meja_id: &str) -> bool {
+    match request_header_or_query_token(req).and_then(|token| decode_token(req, &token)) {
+        Some(claims) if claims.role == "peserta" => claims.sub == meja_id,
+        Some(claims) => AdminRole::parse(&claims.role)
+            .is_some_and(|role| role.allows(Permission::ReadSubmissions)),
+        None => false,
+    }
+}
+
+fn find_meja_file<'a>(state: &'a AppState, meja_id: &str, file_id: &str) -> Option<&'a FileInfo> {
+    state
+        .meja_list
+        .get(meja_id)?
+        .files
+        .iter()
+        .find(|f| f.id == file_id)
+}
+
+// Nama dasar volume RAR: `x.part2.rar` -> `x.part`, `x.rar`/`x.r00` -> `x.r`.
+// Semua volume dari satu archive punya nama dasar yang sama.
+fn rar_volume_base(filename: &str) -> Option<String> {
+    let lower = filename.to_lowercase();
+    if let Some(stem) = lower.strip_suffix(".rar") {
+        if let Some((base, number)) = stem.rsplit_once(".part") {
+            if !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()) {
+                return Some(format!("{}.part", base));
             }
         }
+        return Some(format!("{}.r", stem));
     }
+    let (stem, ext) = lower.rsplit_once('.')?;
+    let digits = ext.strip_prefix('r')?;
+    (digits.len() == 2 && digits.bytes().all(|b| b.is_ascii_digit())).then(|| format!("{}.r", stem))
+}
 
-    HttpResponse::Ok().json(ArchiveContent { files: entries })
+// Folder sementara berisi volume RAR, dihapus saat di-drop
+struct VolumeDir(PathBuf);
+
+impl Drop for VolumeDir {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.0).ok();
+    }
 }
 
-async fn preview_archive_by_path(query: web::Query<HashMap<String, String>>) -> impl Responder {
-    let filepath = match query.get("path") {
-        Some(p) => PathBuf::from(p),
-        None => return HttpResponse::BadRequest().json(serde_json::json!({"error": "Path required"})),
-    };
+// Setiap versi disimpan di foldernya sendiri, sehingga unrar tidak menemukan
+// volume lain dari archive multi-volume. Versi yang dinilai dari setiap volume
+// lain ditautkan ke satu folder sementara bersama file yang dibuka; None jika
+// file bukan bagian dari archive multi-volume.
+fn link_rar_volumes(meja: &Meja, file: &FileInfo, filepath: &Path) -> Option<(VolumeDir, PathBuf)> {
+    let base = rar_volume_base(&file.filename)?;
+    let siblings: Vec<&FileInfo> = versions::by_filename(&meja.files)
+        .into_iter()
+        .filter(|(filename, _)| *filename != file.filename && rar_volume_base(filename).as_deref() == Some(base.as_str()))
+        .filter_map(|(_, file_versions)| versions::submitted(&file_versions))
+        .collect();
+    if siblings.is_empty() {
+        return None;
+    }
 
-    if !filepath.exists() {
-        return HttpResponse::NotFound().json(serde_json::json!({"error": "File not found"}));
+    let dir = VolumeDir(get_storage_path().join("tmp").join(Uuid::new_v4().to_string()));
+    std::fs::create_dir_all(&dir.0).ok()?;
+    std::fs::hard_link(filepath, dir.0.join(&file.filename)).ok()?;
+    for sibling in siblings {
+        if let Some(path) = resolve_storage_path(&sibling.path) {
+            std::fs::hard_link(path, dir.0.join(&sibling.filename)).ok()?;
+        }
     }
+    let path = dir.0.join(&file.filename);
+    Some((dir, path))
+}
 
-    let filename = filepath.file_name().unwrap_or_default().to_string_lossy().to_lowercase();
+// Path untuk membuka file meja sebagai archive, beserta folder volume jika perlu
+fn meja_archive_path(state: &AppState, meja_id: &str, file_id: &str) -> Option<(PathBuf, Option<VolumeDir>)> {
+    let meja = state.meja_list.get(meja_id)?;
+    let file = meja.files.iter().find(|f| f.id == file_id)?;
+    let filepath = resolve_storage_path(&file.path)?;
+    Some(match link_rar_volumes(meja, file, &filepath) {
+        Some((volumes, path)) => (path, Some(volumes)),
+        None => (filepath, None),
+    })
+}
 
-    if filename.ends_with(".zip") {
-        if let Ok(file) = std::fs::File::open(&filepath) {
-            if let Ok(mut archive) = zip::ZipArchive::new(file) {
-                let mut entries = vec![];
-                for i in 0..archive.len() {
-                    if let Ok(file) = archive.by_index(i) {
-                        entries.push(ArchiveEntry {
-                            name: file.name().to_string(),
-                            size: file.size(),
-                            is_dir: file.is_dir(),
-                        });
-                    }
-                }
-                return HttpResponse::Ok().json(ArchiveContent { files: entries });
-            }
+fn archive_error_response(filepath: &Path, error: ArchiveError) -> HttpResponse {
+    match error {
+        ArchiveError::Unsupported => HttpResponse::UnsupportedMediaType()
+            .json(serde_json::json!({"error": "Format archive tidak didukung"})),
+        ArchiveError::EntryNotFound => HttpResponse::NotFound()
+            .json(serde_json::json!({"error": "Entry tidak ditemukan di dalam archive"})),
+        ArchiveError::Encrypted => HttpResponse::UnprocessableEntity()
+            .json(serde_json::json!({"error": "Entry terenkripsi dan tidak bisa dibuka tanpa password"})),
+        ArchiveError::UnsafeName => HttpResponse::BadRequest()
+            .json(serde_json::json!({"error": "Nama entry tidak aman"})),
+        ArchiveError::LimitExceeded(reason) => HttpResponse::UnprocessableEntity()
+            .json(serde_json::json!({"error": format!("Entry ditolak: {}", reason)})),
+        e => {
+            eprintln!("Archive {}: {}", filepath.display(), e);
+            HttpResponse::UnprocessableEntity()
+                .json(serde_json::json!({"error": "Archive rusak atau tidak dapat dibaca"}))
         }
     }
+}
+
+// Format ditentukan dari magic bytes, bukan dari ekstensi nama file. Listing
+// bisa mendekompresi seluruh stream (tar.gz/tar.xz) dan archive di dalamnya,
+// jadi dijalankan di thread blocking agar worker tetap melayani request lain.
+async fn preview_archive_at(filepath: PathBuf) -> HttpResponse {
+    let listing = {
+        let filepath = filepath.clone();
+        web::block(move || archive::list_archive(&filepath)).await
+    };
+    match listing {
+        Ok(Ok(content)) => HttpResponse::Ok().json(content),
+        Ok(Err(e)) => archive_error_response(&filepath, e),
+        Err(_) => HttpResponse::InternalServerError().json(serde_json::json!({"error": "Failed to read archive"})),
+    }
+}
+
+// Meneruskan potongan isi entry dari thread blocking ke body response
+struct ChannelWriter(tokio::sync::mpsc::Sender<Result<web::Bytes, ArchiveError>>);
+
+impl std::io::Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0
+            .blocking_send(Ok(web::Bytes::copy_from_slice(buf)))
+            .map_err(|_| std::io::Error::new(std::io::ErrorKind::BrokenPipe, "koneksi ditutup"))?;
+        Ok(buf.len())
+    }
 
-    HttpResponse::Ok().json(ArchiveContent { files: vec![] })
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
 }
 
-async fn preview_file_content(query: web::Query<HashMap<String, String>>) -> impl Responder {
-    let filepath = match query.get("path") {
-        Some(p) => PathBuf::from(p),
-        None => return HttpResponse::BadRequest().json(serde_json::json!({"error": "Path required"})),
+// Entry teks dikirim sebagai FilePreview; entry biner (atau `download=true`)
+// di-stream langsung tanpa mengekstrak seluruh archive. `volumes` ditahan
+// sampai ekstraksi selesai.
+async fn archive_entry_at(filepath: PathBuf, volumes: Option<VolumeDir>, query: ArchiveEntryQuery) -> HttpResponse {
+    let resolved = {
+        let (filepath, name) = (filepath.clone(), query.name.clone());
+        web::block(move || archive::resolve_entry(&filepath, &name)).await
+    };
+    let resolved = match resolved {
+        Ok(Ok(resolved)) => resolved,
+        Ok(Err(e)) => return archive_error_response(&filepath, e),
+        Err(_) => return HttpResponse::InternalServerError().json(serde_json::json!({"error": "Failed to read archive"})),
     };
 
-    if !filepath.exists() {
-        return HttpResponse::NotFound().json(serde_json::json!({"error": "File not found"}));
+    let filename = query.name.rsplit('/').next().unwrap_or(&query.name).to_string();
+    let size = resolved.entry.size;
+    let is_text = is_text_filename(&filename);
+
+    if is_text && !query.download {
+        let content = if size < TEXT_PREVIEW_LIMIT {
+            match web::block(move || archive::read_entry(&resolved.path, &resolved.entry, TEXT_PREVIEW_LIMIT)).await {
+                Ok(Ok(data)) => data.and_then(|bytes| String::from_utf8(bytes).ok()),
+                Ok(Err(e)) => return archive_error_response(&filepath, e),
+                Err(_) => return HttpResponse::InternalServerError().json(serde_json::json!({"error": "Failed to read archive"})),
+            }
+        } else {
+            None
+        };
+        return HttpResponse::Ok().json(FilePreview {
+            filename,
+            content,
+            is_text,
+            size,
+        });
     }
 
-    let filename = filepath.file_name().unwrap_or_default().to_string_lossy().to_string();
-    let metadata = tokio::fs::metadata(&filepath).await.ok();
-    let size = metadata.map(|m| m.len()).unwrap_or(0);
+    let (tx, mut rx) = tokio::sync::mpsc::channel(16);
+    tokio::task::spawn_blocking(move || {
+        let _volumes = volumes;
+        let mut out = std::io::BufWriter::with_capacity(256 * 1024, ChannelWriter(tx.clone()));
+        let result = archive::extract_entry(&resolved.path, &resolved.entry, &mut out)
+            .and_then(|_| std::io::Write::flush(&mut out).map_err(ArchiveError::Io));
+        if let Err(e) = result {
+            tx.blocking_send(Err(e)).ok();
+        }
+    });
+
+    // Potongan pertama ditunggu dulu agar error (mis. entry terenkripsi)
+    // masih bisa dikirim sebagai status HTTP, bukan body yang terpotong
+    let first = match rx.recv().await {
+        Some(Ok(chunk)) => Some(chunk),
+        Some(Err(e)) => return archive_error_response(&filepath, e),
+        None => None,
+    };
+    let rest = futures_util::stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|item| (item, rx)) });
+    let body = futures_util::stream::iter(first.map(Ok)).chain(rest);
+
+    HttpResponse::Ok()
+        .content_type(mime_guess::from_path(&filename).first_or_octet_stream().to_string())
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(filename)],
+        })
+        .streaming(body)
+}
 
+// Batas ukuran isi file teks yang dikirim sebagai preview
+const TEXT_PREVIEW_LIMIT: u64 = 1_000_000;
+
+fn is_text_filename(filename: &str) -> bool {
     let text_extensions = ["txt", "html", "css", "js", "ts", "tsx", "jsx", "json", "xml", "md", "py", "rs", "c", "cpp", "h", "java", "php", "sql", "sh", "bat", "yml", "yaml", "toml", "ini", "cfg", "log"];
-    let ext = filepath.extension().unwrap_or_default().to_string_lossy().to_lowercase();
-    let is_text = text_extensions.contains(&ext.as_str());
+    let ext = Path::new(filename).extension().unwrap_or_default().to_string_lossy().to_lowercase();
+    text_extensions.contains(&ext.as_str())
+}
+
+async fn preview_file_at(filepath: &Path, filename: &str) -> HttpResponse {
+    let metadata = tokio::fs::metadata(filepath).await.ok();
+    let size = metadata.map(|m| m.len()).unwrap_or(0);
 
-    let content = if is_text && size < 1_000_000 {
-        tokio::fs::read_to_string(&filepath).await.ok()
+    let is_text = is_text_filename(filename);
+
+    let content = if is_text && size < TEXT_PREVIEW_LIMIT {
+        tokio::fs::read_to_string(filepath).await.ok()
     } else {
         None
     };
 
     HttpResponse::Ok().json(FilePreview {
-        filename,
+        filename: filename.to_string(),
         content,
         is_text,
         size,
     })
 }
 
+async fn preview_archive(
+    req: HttpRequest,
+    shared: web::Data<Arc<SharedState>>,
+    path: web::Path<(String, String)>,
+) -> impl Responder {
+    let (meja_id, file_id) = path.into_inner();
+    if let Err(resp) = authorize_meja_access(&req, &meja_id) {
+        return resp;
+    }
+
+    let archive_path = meja_archive_path(&*shared.state.read().await, &meja_id, &file_id);
+    // Folder volume harus tetap ada selama listing berjalan
+    let Some((filepath, _volumes)) = archive_path else {
+        return HttpResponse::NotFound().json(serde_json::json!({"error": "File not found"}));
+    };
+
+    preview_archive_at(filepath).await
+}
+
+async fn preview_soal_archive(
+    shared: web::Data<Arc<SharedState>>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let soal_id = path.into_inner();
+
+    let filepath = {
+        let state = shared.state.read().await;
+        let Some(soal) = state.soal_files.iter().find(|s| s.id == soal_id) else {
+            return HttpResponse::NotFound().json(serde_json::json!({"error": "File not found"}));
+        };
+        resolve_storage_path(&soal.path)
+    };
+    let Some(filepath) = filepath else {
+        return HttpResponse::NotFound().json(serde_json::json!({"error": "File not found"}));
+    };
+
+    preview_archive_at(filepath).await
+}
+
+async fn preview_archive_entry(
+    req: HttpRequest,
+    shared: web::Data<Arc<SharedState>>,
+    path: web::Path<(String, String)>,
+    query: web::Query<ArchiveEntryQuery>,
+) -> impl Responder {
+    let (meja_id, file_id) = path.into_inner();
+    if !can_read_meja_files(&req, &meja_id) {
+        return HttpResponse::Unauthorized().json(serde_json::json!({"error": "Unauthorized"}));
+    }
+
+    let archive_path = meja_archive_path(&*shared.state.read().await, &meja_id, &file_id);
+    let Some((filepath, volumes)) = archive_path else {
+        return HttpResponse::NotFound().json(serde_json::json!({"error": "File not found"}));
+    };
+
+    archive_entry_at(filepath, volumes, query.into_inner()).await
+}
+
+async fn preview_soal_archive_entry(
+    shared: web::Data<Arc<SharedState>>,
+    path: web::Path<String>,
+    query: web::Query<ArchiveEntryQuery>,
+) -> impl Responder {
+    let soal_id = path.into_inner();
+
+    let filepath = {
+        let state = shared.state.read().await;
+        let Some(soal) = state.soal_files.iter().find(|s| s.id == soal_id) else {
+            return HttpResponse::NotFound().json(serde_json::json!({"error": "File not found"}));
+        };
+        resolve_storage_path(&soal.path)
+    };
+    let Some(filepath) = filepath else {
+        return HttpResponse::NotFound().json(serde_json::json!({"error": "File not found"}));
+    };
+
+    archive_entry_at(filepath, None, query.into_inner()).await
+}
+
+async fn preview_meja_file(
+    req: HttpRequest,
+    shared: web::Data<Arc<SharedState>>,
+    path: web::Path<(String, String)>,
+) -> impl Responder {
+    let (meja_id, file_id) = path.into_inner();
+    if let Err(resp) = authorize_meja_access(&req, &meja_id) {
+        return resp;
+    }
+
+    let (filepath, filename) = {
+        let state = shared.state.read().await;
+        let Some(file) = find_meja_file(&state, &meja_id, &file_id) else {
+            return HttpResponse::NotFound().json(serde_json::json!({"error": "File not found"}));
+        };
+        (resolve_storage_path(&file.path), file.filename.clone())
+    };
+    let Some(filepath) = filepath else {
+        return HttpResponse::NotFound().json(serde_json::json!({"error": "File not found"}));
+    };
+
+    preview_file_at(&filepath, &filename).await
+}
+
+async fn preview_soal_file(
+    shared: web::Data<Arc<SharedState>>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let soal_id = path.into_inner();
+
+    let (filepath, filename) = {
+        let state = shared.state.read().await;
+        let Some(soal) = state.soal_files.iter().find(|s| s.id == soal_id) else {
+            return HttpResponse::NotFound().json(serde_json::json!({"error": "File not found"}));
+        };
+        (resolve_storage_path(&soal.path), soal.filename.clone())
+    };
+    let Some(filepath) = filepath else {
+        return HttpResponse::NotFound().json(serde_json::json!({"error": "File not found"}));
+    };
+
+    preview_file_at(&filepath, &filename).await
+}
+
 // === WebSocket for Real-time Updates ===
 
+// Token yang sudah tidak berlaku diperlakukan sama seperti saat connect: view publik
+fn socket_view_role(shared: &SharedState, token: Option<&str>) -> ViewRole {
+    token
+        .and_then(|token| shared.auth.read().ok()?.decode_token(token))
+        .map(view_role_from_claims)
+        .unwrap_or(ViewRole::Public)
+}
+
 async fn ws_handler(
     req: HttpRequest,
     stream: web::Payload,
     shared: web::Data<Arc<SharedState>>,
 ) -> Result<HttpResponse, actix_web::Error> {
+    let token = request_header_or_query_token(&req);
+    let role = socket_view_role(&shared, token.as_deref());
     let (res, mut session, mut stream) = actix_ws::handle(&req, stream)?;
 
     let mut rx = shared.broadcast_tx.subscribe();
@@ -1152,7 +3367,7 @@ async fn ws_handler(
     // Kirim state awal ke client baru
     {
         let state = shared.state.read().await;
-        if let Ok(json) = serde_json::to_string(&*state) {
+        if let Ok(json) = serde_json::to_string(&StateView::new(&state, &role)) {
             let _ = session.text(json).await;
         }
     }
@@ -1163,21 +3378,24 @@ async fn ws_handler(
             tokio::select! {
                 msg = rx.recv() => {
                     match msg {
-                        Ok(text) => {
-                            if session.text(text).await.is_err() {
+                        Ok(frame) => {
+                            // Token dicek ulang setiap frame supaya logout, cabut sesi,
+                            // rotasi kunci, atau perubahan akun langsung berlaku
+                            let role = socket_view_role(&shared, token.as_deref());
+                            let Some(json) = frame.view(&shared, &role).await else {
+                                continue;
+                            };
+                            if session.text(json).await.is_err() {
                                 break;
                             }
                         }
+                        Err(broadcast::error::RecvError::Lagged(_)) => continue,
                         Err(_) => break,
                     }
                 }
                 msg = stream.next() => {
                     match msg {
-                        Some(Ok(Message::Ping(bytes))) => {
-                            if session.pong(&bytes).await.is_err() {
-                                break;
-                            }
-                        }
+                        Some(Ok(Message::Ping(bytes))) if session.pong(&bytes).await.is_err() => break,
                         Some(Ok(Message::Close(_))) | None => break,
                         _ => {}
                     }
@@ -1190,9 +3408,23 @@ async fn ws_handler(
 }
 
 // === Global Timer Task - Hanya berjalan SEKALI ===
+// Sisa detik meja yang masih berjalan karena tambahan waktu setelah timer global habis
+fn extended_remaining(state: &AppState) -> Vec<i64> {
+    let now = Utc::now();
+    state
+        .meja_list
+        .values()
+        .filter(|meja| !meja.extensions.is_empty())
+        .filter_map(|meja| state.timer.deadline(meja.extension_seconds()))
+        .filter(|deadline| *deadline > now)
+        .map(|deadline| (deadline.signed_duration_since(now).num_milliseconds() + 999) / 1000)
+        .collect()
+}
+
 async fn start_global_timer_task(shared: Arc<SharedState>) {
-    let mut interval = tokio::time::interval(tokio::time::Duration::from_millis(TIMER_BROADCAST_INTERVAL_MS));
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_millis(settings().timer_broadcast_interval_ms));
     let mut last_remaining: i64 = -1;
+    let mut last_extended: Vec<i64> = vec![];
     
     loop {
         interval.tick().await;
@@ -1200,46 +3432,51 @@ async fn start_global_timer_task(shared: Arc<SharedState>) {
         let should_broadcast = {
             let mut state = shared.state.write().await;
             if state.timer.is_running {
-                if let Some(started) = state.timer.started_at {
-                    let elapsed = Utc::now().signed_duration_since(started).num_seconds();
-                    let remaining = (state.timer.duration_seconds - elapsed).max(0);
-                    
-                    // Hanya update jika nilai berubah
-                    if remaining != last_remaining {
-                        last_remaining = remaining;
-                        state.timer.remaining_seconds = remaining;
-                        
-                        if remaining <= 0 {
-                            state.timer.is_running = false;
-                            state.timer.started_at = None;
-                        }
-                        true
-                    } else {
-                        false
-                    }
-                } else {
-                    false
+                if state.timer.sync(Utc::now()) {
+                    // Status tenggang ikut tersimpan jika server restart
+                    save_timer_to_db(&*shared.db.lock().await, &state.timer);
                 }
+
+                // Hanya broadcast jika nilai berubah
+                let remaining = state.timer.remaining_seconds;
+                let changed = remaining != last_remaining;
+                last_remaining = remaining;
+                changed
             } else {
                 last_remaining = state.timer.remaining_seconds;
-                false
+                // Timer global berhenti, tapi meja dengan tambahan waktu masih berjalan
+                let extended = extended_remaining(&state);
+                let changed = extended != last_extended;
+                last_extended = extended;
+                changed
             }
         };
         
         if should_broadcast {
-            broadcast_timer_only(&shared).await;
+            broadcast_state(&shared).await;
         }
     }
 }
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
+    match Settings::load() {
+        Ok(loaded) => config::init(loaded),
+        Err(e) => {
+            eprintln!("Konfigurasi tidak valid: {}", e);
+            std::process::exit(1);
+        }
+    }
+    let cfg = settings();
+
     println!("===========================================");
     println!("  Lomba Coding Server - OPTIMIZED");
     println!("===========================================");
-    println!("Server: http://localhost:3001");
+    println!("Server: http://{}", cfg.bind_address());
+    println!("Storage: {}", cfg.storage_dir.display());
     println!("Default admin: admin / admin123");
-    println!("Timer broadcast: {}ms interval", TIMER_BROADCAST_INTERVAL_MS);
+    println!("Timer broadcast: {}ms interval", cfg.timer_broadcast_interval_ms);
+    println!("Max file size: {}MB, workers: {}", cfg.max_file_size_mb(), cfg.workers);
     println!("===========================================");
 
     get_storage_path();
@@ -1248,16 +3485,28 @@ async fn main() -> std::io::Result<()> {
     let db_path = get_storage_path().join("lomba.db");
     let conn = Connection::open(&db_path).expect("Failed to open database");
     init_database(&conn).expect("Failed to initialize database");
+    auth::init_auth_tables(&conn).expect("Failed to initialize auth tables");
+    resumable::init_upload_tables(&conn).expect("Failed to initialize upload tables");
+    receipt::init_receipt_tables(&conn).expect("Failed to initialize receipt tables");
+    audit::init_audit_tables(&conn).expect("Failed to initialize audit tables");
+    rules::init_rules_tables(&conn).expect("Failed to initialize upload rules");
+    extensions::init_extension_tables(&conn).expect("Failed to initialize time extension tables");
+    let receipt_key = receipt::load_or_create_key(&conn).expect("Failed to load receipt key");
+    let auth_state = AuthState::load(&conn).expect("Failed to load JWT keys");
 
     let initial_state = load_state_from_db(&conn);
 
     // Buffer lebih besar untuk broadcast channel
-    let (broadcast_tx, _) = broadcast::channel::<String>(256);
+    let (broadcast_tx, _) = broadcast::channel::<Arc<StateFrame>>(256);
 
     let shared_state = Arc::new(SharedState {
         state: RwLock::new(initial_state),
         broadcast_tx,
         db: Mutex::new(conn),
+        auth: std::sync::RwLock::new(auth_state),
+        login_limiter: std::sync::Mutex::new(LoginLimiter::default()),
+        upload_locks: UploadLocks::default(),
+        receipt_key,
     });
 
     // Start SINGLE global timer task
@@ -1266,6 +3515,17 @@ async fn main() -> std::io::Result<()> {
         start_global_timer_task(timer_shared).await;
     });
 
+    // Sesi upload bertahap yang ditinggalkan dibuang berkala
+    let sweep_shared = shared_state.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(10 * 60));
+        loop {
+            interval.tick().await;
+            let db = sweep_shared.db.lock().await;
+            resumable::sweep_expired(&db, &sweep_shared.upload_locks);
+        }
+    });
+
     let server_shared = shared_state.clone();
 
     HttpServer::new(move || {
@@ -1275,12 +3535,12 @@ async fn main() -> std::io::Result<()> {
             .allow_any_header();
 
         let payload_config = web::PayloadConfig::default()
-            .limit(300 * 1024 * 1024);
+            .limit(cfg.max_file_size as usize);
 
         // Multipart config untuk upload lebih cepat
         let multipart_config = actix_multipart::form::MultipartFormConfig::default()
-            .total_limit(300 * 1024 * 1024)
-            .memory_limit(50 * 1024 * 1024); // 50MB memory buffer
+            .total_limit(cfg.max_file_size as usize)
+            .memory_limit(cfg.multipart_memory_limit);
 
         App::new()
             .wrap(cors)
@@ -1290,31 +3550,61 @@ async fn main() -> std::io::Result<()> {
             .route("/api/auth/login", web::post().to(admin_login))
             .route("/api/auth/verify", web::get().to(verify_token))
             .route("/api/auth/change-password", web::post().to(change_password))
+            .route("/api/auth/logout", web::post().to(logout))
+            .route("/api/admin/auth/rotate-key", web::post().to(rotate_jwt_key))
             .route("/api/state", web::get().to(get_state))
+            .route("/api/admin/lockouts", web::get().to(list_lockouts))
+            .route("/api/admin/lockouts/lift", web::post().to(lift_lockout))
+            .route("/api/admin/users", web::get().to(list_admins))
+            .route("/api/admin/users", web::post().to(create_admin))
+            .route("/api/admin/users/{id}", web::put().to(update_admin))
+            .route("/api/admin/users/{id}", web::delete().to(delete_admin))
             .route("/api/admin/meja/generate", web::post().to(generate_meja))
             .route("/api/admin/meja/export", web::get().to(export_meja))
             .route("/api/admin/meja/export/json", web::get().to(export_meja_json))
+            .route("/api/admin/meja/export/zip", web::get().to(export_submissions_zip))
+            .route("/api/admin/meja/{id}/reopen", web::post().to(reopen_meja))
+            .route("/api/admin/meja/{id}/extensions", web::post().to(grant_extension))
+            .route("/api/admin/meja/{id}/extensions/{extension_id}", web::delete().to(revoke_extension))
+            .route("/api/admin/meja/{meja_id}/files/{file_id}/diff", web::get().to(diff_meja_file))
+            .route("/api/admin/receipts/verify", web::post().to(verify_receipt))
+            .route("/api/admin/audit/files", web::get().to(list_withdrawn_files))
+            .route("/api/admin/upload-rules", web::put().to(set_upload_rules))
             .route("/api/admin/timer/set", web::post().to(set_timer))
             .route("/api/admin/timer/start", web::post().to(start_timer))
             .route("/api/admin/timer/pause", web::post().to(pause_timer))
             .route("/api/admin/timer/reset", web::post().to(reset_timer))
             .route("/api/admin/timer/adjust", web::post().to(adjust_timer))
+            .route("/api/admin/timer/grace", web::post().to(set_grace_period))
             .route("/api/admin/soal/upload", web::post().to(upload_soal))
             .route("/api/admin/soal/{id}", web::delete().to(delete_soal))
             .route("/api/login", web::post().to(login_peserta))
             .route("/api/meja/{id}", web::get().to(get_meja))
             .route("/api/meja/{id}/update", web::post().to(update_peserta))
+            .route("/api/meja/{id}/submit", web::post().to(submit_meja))
             .route("/api/meja/{id}/upload", web::post().to(upload_file))
+            .route("/api/meja/{id}/uploads", web::post().to(init_upload))
+            .route("/api/meja/{id}/uploads/{upload_id}", web::get().to(get_upload))
+            .route("/api/meja/{id}/uploads/{upload_id}", web::patch().to(upload_chunk))
+            .route("/api/meja/{id}/uploads/{upload_id}", web::delete().to(cancel_upload))
+            .route("/api/meja/{id}/uploads/{upload_id}/finalize", web::post().to(finalize_upload))
+            .route("/api/meja/{meja_id}/files/{file_id}", web::delete().to(delete_meja_file))
+            .route("/api/meja/{meja_id}/files/{file_id}", web::put().to(replace_meja_file))
+            .route("/api/meja/{meja_id}/files/{file_id}/download", web::get().to(download_meja_file))
+            .route("/api/meja/{meja_id}/files/{file_id}/final", web::post().to(mark_file_final))
+            .route("/api/meja/{meja_id}/files/{file_id}/receipt", web::get().to(get_file_receipt))
             .route("/api/soal", web::get().to(get_soal_list))
             .route("/api/soal/{id}/download", web::get().to(download_soal))
+            .route("/api/archive/preview/soal/{soal_id}", web::get().to(preview_soal_archive))
             .route("/api/archive/preview/{meja_id}/{file_id}", web::get().to(preview_archive))
-            .route("/api/archive/preview", web::get().to(preview_archive_by_path))
-            .route("/api/file/preview", web::get().to(preview_file_content))
+            .route("/api/archive/entry/soal/{soal_id}", web::get().to(preview_soal_archive_entry))
+            .route("/api/archive/entry/{meja_id}/{file_id}", web::get().to(preview_archive_entry))
+            .route("/api/file/preview/soal/{soal_id}", web::get().to(preview_soal_file))
+            .route("/api/file/preview/{meja_id}/{file_id}", web::get().to(preview_meja_file))
             .route("/ws", web::get().to(ws_handler))
-            .service(Files::new("/storage", "./storage"))
     })
-    .workers(4) // Optimal untuk konkurensi
-    .bind("0.0.0.0:3001")?
+    .workers(cfg.workers)
+    .bind(cfg.bind_address())?
     .run()
     .await
 }
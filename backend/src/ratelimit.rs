@@ -0,0 +1,337 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+// Percobaan gagal yang masih dibiarkan sebelum lockout pertama
+const FREE_ATTEMPTS: u32 = 5;
+const BASE_LOCKOUT_SECONDS: i64 = 30;
+const MAX_LOCKOUT_SECONDS: i64 = 60 * 60;
+// Counter gagal direset jika tidak ada percobaan gagal selama ini
+const FAILURE_WINDOW_SECONDS: i64 = 15 * 60;
+const MAX_EVENTS: usize = 200;
+// Batas key yang dilacak; username dari request tanpa auth bisa apa saja
+const MAX_TRACKED_KEYS: usize = 10_000;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitScope {
+    AdminIp,
+    AdminUsername,
+    PesertaIp,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Deserialize)]
+pub struct LimitKey {
+    pub scope: LimitScope,
+    pub value: String,
+}
+
+impl LimitKey {
+    pub fn new(scope: LimitScope, value: &str) -> Self {
+        LimitKey {
+            scope,
+            value: value.to_string(),
+        }
+    }
+}
+
+struct Attempts {
+    failures: u32,
+    // Percobaan yang sudah lolos pengecekan tapi hasilnya belum dicatat
+    pending: u32,
+    last_failure: DateTime<Utc>,
+    locked_until: Option<DateTime<Utc>>,
+}
+
+impl Attempts {
+    // Window dihitung dari akhir lockout, supaya lockout panjang tidak langsung mereset backoff
+    fn is_stale(&self, now: DateTime<Utc>) -> bool {
+        let last_activity = self
+            .locked_until
+            .map_or(self.last_failure, |until| until.max(self.last_failure));
+        self.pending == 0 && now - last_activity > Duration::seconds(FAILURE_WINDOW_SECONDS)
+    }
+
+    // Percobaan yang boleh berjalan bersamaan: sisa percobaan gratis, atau
+    // satu percobaan setelah lockout berakhir
+    fn allowed_pending(&self) -> u32 {
+        (FREE_ATTEMPTS + 1).saturating_sub(self.failures).max(1)
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct Lockout {
+    pub scope: LimitScope,
+    pub value: String,
+    pub failures: u32,
+    pub locked_until: DateTime<Utc>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct LockoutEvent {
+    pub scope: LimitScope,
+    pub value: String,
+    pub failures: u32,
+    pub locked_at: DateTime<Utc>,
+    pub locked_until: DateTime<Utc>,
+    pub lifted_by: Option<String>,
+}
+
+#[derive(Default)]
+pub struct LoginLimiter {
+    attempts: HashMap<LimitKey, Attempts>,
+    events: VecDeque<LockoutEvent>,
+}
+
+fn lockout_duration(failures: u32) -> Duration {
+    let exponent = failures.saturating_sub(FREE_ATTEMPTS + 1).min(16);
+    let seconds = BASE_LOCKOUT_SECONDS.saturating_mul(1 << exponent);
+    Duration::seconds(seconds.min(MAX_LOCKOUT_SECONDS))
+}
+
+impl LoginLimiter {
+    // Sisa waktu lockout terlama di antara semua key, jika ada yang terkunci
+    pub fn check(&self, keys: &[LimitKey], now: DateTime<Utc>) -> Option<Duration> {
+        keys.iter()
+            .filter_map(|key| self.attempts.get(key)?.locked_until)
+            .filter(|until| *until > now)
+            .map(|until| until - now)
+            .max()
+    }
+
+    // Cek lockout dan pesan satu percobaan sekaligus, supaya request paralel
+    // tidak lolos semua sebelum kegagalan pertama tercatat. Hasilnya wajib
+    // dilaporkan lewat `record_failure` atau `release`. Err berisi waktu tunggu.
+    pub fn begin_attempt(&mut self, keys: &[LimitKey], now: DateTime<Utc>) -> Result<(), Duration> {
+        if let Some(remaining) = self.check(keys, now) {
+            return Err(remaining);
+        }
+        let busy = keys
+            .iter()
+            .filter_map(|key| self.attempts.get(key))
+            .any(|attempts| attempts.pending >= attempts.allowed_pending());
+        if busy {
+            return Err(Duration::seconds(1));
+        }
+        for key in keys {
+            self.entry(key, now).pending += 1;
+        }
+        Ok(())
+    }
+
+    // Percobaan selesai tanpa gagal; counter `cleared` ikut direset
+    pub fn release(&mut self, keys: &[LimitKey], cleared: Option<&LimitKey>) {
+        for key in keys {
+            if let Some(attempts) = self.attempts.get_mut(key) {
+                attempts.pending = attempts.pending.saturating_sub(1);
+            }
+        }
+        if let Some(key) = cleared {
+            self.attempts.remove(key);
+        }
+    }
+
+    pub fn record_failure(&mut self, keys: &[LimitKey], now: DateTime<Utc>) {
+        self.prune(now);
+        for key in keys {
+            let attempts = self.entry(key, now);
+            attempts.pending = attempts.pending.saturating_sub(1);
+            attempts.failures += 1;
+            attempts.last_failure = now;
+
+            let failures = attempts.failures;
+            if failures > FREE_ATTEMPTS {
+                let locked_until = now + lockout_duration(failures);
+                attempts.locked_until = Some(locked_until);
+                self.events.push_back(LockoutEvent {
+                    scope: key.scope,
+                    value: key.value.clone(),
+                    failures,
+                    locked_at: now,
+                    locked_until,
+                    lifted_by: None,
+                });
+                if self.events.len() > MAX_EVENTS {
+                    self.events.pop_front();
+                }
+            }
+        }
+    }
+
+    // Buka lockout secara manual; counter gagal ikut direset
+    pub fn lift(&mut self, key: &LimitKey, lifted_by: &str) -> bool {
+        if self.attempts.remove(key).is_none() {
+            return false;
+        }
+        for event in self.events.iter_mut().rev() {
+            if event.scope == key.scope && event.value == key.value && event.lifted_by.is_none() {
+                event.lifted_by = Some(lifted_by.to_string());
+            }
+        }
+        true
+    }
+
+    pub fn active_lockouts(&self, now: DateTime<Utc>) -> Vec<Lockout> {
+        let mut lockouts: Vec<Lockout> = self
+            .attempts
+            .iter()
+            .filter_map(|(key, attempts)| {
+                let locked_until = attempts.locked_until.filter(|until| *until > now)?;
+                Some(Lockout {
+                    scope: key.scope,
+                    value: key.value.clone(),
+                    failures: attempts.failures,
+                    locked_until,
+                })
+            })
+            .collect();
+        lockouts.sort_by_key(|l| std::cmp::Reverse(l.locked_until));
+        lockouts
+    }
+
+    pub fn recent_events(&self) -> Vec<LockoutEvent> {
+        self.events.iter().rev().cloned().collect()
+    }
+
+    // Counter milik `key`; counter yang sudah lewat window dimulai dari nol
+    fn entry(&mut self, key: &LimitKey, now: DateTime<Utc>) -> &mut Attempts {
+        if !self.attempts.contains_key(key) && self.attempts.len() >= MAX_TRACKED_KEYS {
+            self.evict_one(now);
+        }
+        let attempts = self.attempts.entry(key.clone()).or_insert(Attempts {
+            failures: 0,
+            pending: 0,
+            last_failure: now,
+            locked_until: None,
+        });
+        if attempts.is_stale(now) {
+            attempts.failures = 0;
+            attempts.locked_until = None;
+        }
+        attempts
+    }
+
+    // Buang counter yang sudah lewat window
+    fn prune(&mut self, now: DateTime<Utc>) {
+        self.attempts.retain(|_, attempts| !attempts.is_stale(now));
+    }
+
+    // Buang counter dengan kegagalan terlama; yang sedang dipakai atau terkunci
+    // didahulukan untuk dipertahankan
+    fn evict_one(&mut self, now: DateTime<Utc>) {
+        let victim = self
+            .attempts
+            .iter()
+            .min_by_key(|(_, attempts)| {
+                let locked = attempts.locked_until.is_some_and(|until| until > now);
+                (attempts.pending > 0, locked, attempts.last_failure)
+            })
+            .map(|(key, _)| key.clone());
+        if let Some(key) = victim {
+            self.attempts.remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2026-01-01T08:00:00Z").unwrap().with_timezone(&Utc) + Duration::seconds(seconds)
+    }
+
+    fn ip(value: &str) -> Vec<LimitKey> {
+        vec![LimitKey::new(LimitScope::PesertaIp, value)]
+    }
+
+    fn fail(limiter: &mut LoginLimiter, keys: &[LimitKey], now: DateTime<Utc>) {
+        limiter.begin_attempt(keys, now).unwrap();
+        limiter.record_failure(keys, now);
+    }
+
+    #[test]
+    fn locks_after_free_attempts_with_doubling_backoff() {
+        let mut limiter = LoginLimiter::default();
+        let keys = ip("10.0.0.1");
+        for _ in 0..FREE_ATTEMPTS {
+            fail(&mut limiter, &keys, at(0));
+        }
+        assert!(limiter.check(&keys, at(0)).is_none());
+
+        fail(&mut limiter, &keys, at(0));
+        assert_eq!(limiter.begin_attempt(&keys, at(1)), Err(Duration::seconds(29)));
+        assert_eq!(limiter.recent_events().len(), 1);
+
+        // Setelah lockout berakhir hanya satu percobaan, dan gagal lagi menggandakan lockout
+        fail(&mut limiter, &keys, at(30));
+        assert_eq!(limiter.check(&keys, at(30)), Some(Duration::seconds(60)));
+        assert_eq!(lockout_duration(40), Duration::seconds(MAX_LOCKOUT_SECONDS));
+    }
+
+    #[test]
+    fn parallel_attempts_cannot_exceed_free_attempts() {
+        let mut limiter = LoginLimiter::default();
+        let keys = ip("10.0.0.1");
+        for _ in 0..=FREE_ATTEMPTS {
+            limiter.begin_attempt(&keys, at(0)).unwrap();
+        }
+        assert_eq!(limiter.begin_attempt(&keys, at(0)), Err(Duration::seconds(1)));
+
+        // Satu yang berhasil membuka satu slot lagi
+        limiter.release(&keys, None);
+        assert!(limiter.begin_attempt(&keys, at(0)).is_ok());
+        for _ in 0..=FREE_ATTEMPTS {
+            limiter.record_failure(&keys, at(0));
+        }
+        assert!(limiter.check(&keys, at(0)).is_some());
+    }
+
+    #[test]
+    fn success_clears_only_the_given_key() {
+        let mut limiter = LoginLimiter::default();
+        let ip_key = LimitKey::new(LimitScope::AdminIp, "10.0.0.1");
+        let user_key = LimitKey::new(LimitScope::AdminUsername, "admin");
+        let keys = [ip_key.clone(), user_key.clone()];
+        for _ in 0..3 {
+            fail(&mut limiter, &keys, at(0));
+        }
+        limiter.begin_attempt(&keys, at(0)).unwrap();
+        limiter.release(&keys, Some(&user_key));
+
+        assert!(!limiter.attempts.contains_key(&user_key));
+        let ip_attempts = &limiter.attempts[&ip_key];
+        assert_eq!((ip_attempts.failures, ip_attempts.pending), (3, 0));
+    }
+
+    #[test]
+    fn failures_reset_after_window() {
+        let mut limiter = LoginLimiter::default();
+        let keys = ip("10.0.0.1");
+        for _ in 0..FREE_ATTEMPTS {
+            fail(&mut limiter, &keys, at(0));
+        }
+        let later = at(FAILURE_WINDOW_SECONDS + 1);
+        fail(&mut limiter, &keys, later);
+        assert!(limiter.check(&keys, later).is_none());
+        assert_eq!(limiter.attempts[&keys[0]].failures, 1);
+    }
+
+    #[test]
+    fn eviction_keeps_locked_and_pending_keys() {
+        let mut limiter = LoginLimiter::default();
+        let locked = ip("locked");
+        for _ in 0..=FREE_ATTEMPTS {
+            fail(&mut limiter, &locked, at(0));
+        }
+        let pending = ip("pending");
+        limiter.begin_attempt(&pending, at(0)).unwrap();
+        for i in 0..MAX_TRACKED_KEYS {
+            fail(&mut limiter, &ip(&i.to_string()), at(1));
+        }
+
+        assert_eq!(limiter.attempts.len(), MAX_TRACKED_KEYS);
+        assert!(limiter.check(&locked, at(2)).is_some());
+        assert!(limiter.attempts.contains_key(&pending[0]));
+    }
+}
@@ -0,0 +1,122 @@
+use crate::{digest, FileInfo, Meja};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+// Bukti upload yang disimpan peserta. Ditandatangani dengan kunci server
+// tersendiri (bukan kunci JWT) supaya tetap bisa diverifikasi setelah kunci
+// JWT dirotasi.
+#[derive(Serialize, Deserialize)]
+pub struct Receipt {
+    pub file_id: String,
+    pub meja_id: String,
+    pub meja_nomor: u32,
+    pub filename: String,
+    pub version: u32,
+    pub size: u64,
+    pub sha256: String,
+    pub uploaded_at: DateTime<Utc>,
+    pub signature: String,
+}
+
+// === Database ===
+
+pub fn init_receipt_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS receipt_key (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            secret TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+pub fn load_or_create_key(conn: &Connection) -> rusqlite::Result<String> {
+    let existing: Option<String> = conn
+        .query_row("SELECT secret FROM receipt_key WHERE id = 1", [], |row| row.get(0))
+        .optional()?;
+    if let Some(secret) = existing {
+        return Ok(secret);
+    }
+
+    let bytes: [u8; 32] = rand::thread_rng().gen();
+    let secret = digest::hex(&bytes);
+    conn.execute(
+        "INSERT INTO receipt_key (id, secret, created_at) VALUES (1, ?1, ?2)",
+        params![secret, Utc::now().to_rfc3339()],
+    )?;
+    Ok(secret)
+}
+
+// === Signing ===
+
+impl Receipt {
+    // None untuk upload lama yang belum punya hash
+    pub fn issue(key: &str, meja: &Meja, file: &FileInfo) -> Option<Receipt> {
+        let mut receipt = Receipt {
+            file_id: file.id.clone(),
+            meja_id: meja.id.clone(),
+            meja_nomor: meja.nomor,
+            filename: file.filename.clone(),
+            version: file.version,
+            size: file.size,
+            sha256: file.sha256.clone()?,
+            uploaded_at: file.uploaded_at,
+            signature: String::new(),
+        };
+        receipt.signature = digest::hex(&receipt.mac(key).finalize().into_bytes());
+        Some(receipt)
+    }
+
+    pub fn verify_signature(&self, key: &str) -> bool {
+        let Some(signature) = decode_hex(&self.signature) else {
+            return false;
+        };
+        self.mac(key).verify_slice(&signature).is_ok()
+    }
+
+    // Field digabung per baris dengan urutan tetap; nama file sudah disanitasi
+    // sehingga tidak mengandung baris baru
+    fn mac(&self, key: &str) -> HmacSha256 {
+        let payload = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}",
+            self.file_id,
+            self.meja_id,
+            self.meja_nomor,
+            self.filename,
+            self.version,
+            self.size,
+            self.sha256,
+            self.uploaded_at.to_rfc3339()
+        );
+        let mut mac = HmacSha256::new_from_slice(key.as_bytes()).expect("HMAC menerima kunci berapa pun panjangnya");
+        mac.update(payload.as_bytes());
+        mac
+    }
+
+    // Tanda tangan valid belum cukup: isi bukti juga harus sama dengan catatan server
+    pub fn matches(&self, meja: &Meja, file: &FileInfo) -> bool {
+        self.meja_id == meja.id
+            && self.meja_nomor == meja.nomor
+            && self.filename == file.filename
+            && self.version == file.version
+            && self.size == file.size
+            && Some(&self.sha256) == file.sha256.as_ref()
+            && self.uploaded_at == file.uploaded_at
+    }
+}
+
+// Panjang ganjil atau karakter non-hex menghasilkan None
+fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(value.get(i..i + 2)?, 16).ok())
+        .collect()
+}
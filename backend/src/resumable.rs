@@ -0,0 +1,191 @@
+use crate::config::settings;
+use crate::rules::MejaUsage;
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+// Upload bertahap: klien membuat sesi, mengirim potongan file dengan PATCH
+// dari offset terakhir, lalu finalize. Offset disimpan di SQLite supaya
+// upload tetap bisa dilanjutkan setelah koneksi putus atau server restart.
+
+// Sesi terbuka per meja; masing-masing memesan ruang sebesar ukuran filenya
+pub const MAX_SESSIONS_PER_MEJA: usize = 3;
+// Sesi tanpa potongan baru selama ini dianggap ditinggalkan dan dibuang
+const SESSION_TTL_HOURS: i64 = 6;
+
+#[derive(Serialize)]
+pub struct UploadSession {
+    pub id: String,
+    #[serde(skip)]
+    pub meja_id: String,
+    pub filename: String,
+    pub size: u64,
+    pub offset: u64,
+}
+
+// === Database ===
+
+pub fn init_upload_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS upload_sessions (
+            id TEXT PRIMARY KEY,
+            meja_id TEXT NOT NULL,
+            filename TEXT NOT NULL,
+            size INTEGER NOT NULL,
+            offset INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (meja_id) REFERENCES meja(id)
+        )",
+        [],
+    )?;
+    crate::add_column_if_missing(conn, "upload_sessions", "updated_at", "TEXT")?;
+    Ok(())
+}
+
+pub fn create_session(conn: &Connection, meja_id: &str, filename: &str, size: u64) -> rusqlite::Result<UploadSession> {
+    let session = UploadSession {
+        id: Uuid::new_v4().to_string(),
+        meja_id: meja_id.to_string(),
+        filename: filename.to_string(),
+        size,
+        offset: 0,
+    };
+    conn.execute(
+        "INSERT INTO upload_sessions (id, meja_id, filename, size, offset, created_at) VALUES (?1, ?2, ?3, ?4, 0, ?5)",
+        params![session.id, session.meja_id, session.filename, size as i64, Utc::now().to_rfc3339()],
+    )?;
+    Ok(session)
+}
+
+pub fn get_session(conn: &Connection, id: &str) -> Option<UploadSession> {
+    conn.query_row(
+        "SELECT id, meja_id, filename, size, offset FROM upload_sessions WHERE id = ?1",
+        params![id],
+        |row| {
+            Ok(UploadSession {
+                id: row.get(0)?,
+                meja_id: row.get(1)?,
+                filename: row.get(2)?,
+                size: row.get(3)?,
+                offset: row.get(4)?,
+            })
+        },
+    )
+    .optional()
+    .ok()
+    .flatten()
+}
+
+pub fn set_offset(conn: &Connection, id: &str, offset: u64) {
+    conn.execute(
+        "UPDATE upload_sessions SET offset = ?1, updated_at = ?2 WHERE id = ?3",
+        params![offset as i64, Utc::now().to_rfc3339(), id],
+    )
+    .ok();
+}
+
+// Ruang yang dipesan sesi terbuka milik meja, selain sesi `except`. Setiap
+// sesi dihitung sebagai satu file dengan ukuran penuh.
+pub fn reserved(conn: &Connection, meja_id: &str, except: Option<&str>) -> MejaUsage {
+    conn.query_row(
+        "SELECT COUNT(*), COALESCE(SUM(size), 0) FROM upload_sessions WHERE meja_id = ?1 AND id IS NOT ?2",
+        params![meja_id, except],
+        |row| {
+            Ok(MejaUsage {
+                files: row.get::<_, i64>(0)? as usize,
+                bytes: row.get::<_, i64>(1)? as u64,
+            })
+        },
+    )
+    .unwrap_or_default()
+}
+
+// Buang sesi yang tidak menerima potongan selama SESSION_TTL_HOURS. Sesi
+// yang sedang diproses request lain dilewati.
+pub fn sweep_expired(conn: &Connection, locks: &UploadLocks) -> usize {
+    let cutoff = Utc::now() - Duration::hours(SESSION_TTL_HOURS);
+    let Ok(mut stmt) = conn.prepare("SELECT id, COALESCE(updated_at, created_at) FROM upload_sessions") else {
+        return 0;
+    };
+    let expired: Vec<String> = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+        .map(|rows| {
+            rows.filter_map(|r| r.ok())
+                .filter(|(_, touched)| {
+                    DateTime::parse_from_rfc3339(touched).map_or(true, |t| t < cutoff)
+                })
+                .map(|(id, _)| id)
+                .filter(|id| !locks.is_active(id))
+                .collect()
+        })
+        .unwrap_or_default();
+    for id in &expired {
+        delete_session(conn, id);
+    }
+    expired.len()
+}
+
+pub fn delete_session(conn: &Connection, id: &str) {
+    conn.execute("DELETE FROM upload_sessions WHERE id = ?1", params![id]).ok();
+    std::fs::remove_file(partial_path(id)).ok();
+}
+
+// Dipakai saat meja di-generate ulang: semua sesi ikut dibuang
+pub fn delete_all_sessions(conn: &Connection) {
+    conn.execute("DELETE FROM upload_sessions", []).ok();
+    std::fs::remove_dir_all(partial_dir()).ok();
+}
+
+// === Storage ===
+
+fn partial_dir() -> PathBuf {
+    settings().storage_dir.join("partial")
+}
+
+// File sementara berisi byte yang sudah diterima, dipindah ke folder meja saat finalize
+pub fn partial_path(id: &str) -> PathBuf {
+    let dir = partial_dir();
+    std::fs::create_dir_all(&dir).ok();
+    dir.join(id)
+}
+
+// === Locks ===
+
+// Satu sesi hanya boleh menerima satu PATCH/finalize dalam satu waktu,
+// supaya dua request yang sama-sama di-retry tidak menulis offset yang sama
+#[derive(Default)]
+pub struct UploadLocks(Mutex<HashSet<String>>);
+
+pub struct UploadLockGuard<'a> {
+    locks: &'a UploadLocks,
+    id: String,
+}
+
+impl UploadLocks {
+    fn is_active(&self, id: &str) -> bool {
+        self.0.lock().map_or(true, |active| active.contains(id))
+    }
+
+    pub fn acquire(&self, id: &str) -> Option<UploadLockGuard<'_>> {
+        let mut active = self.0.lock().ok()?;
+        if !active.insert(id.to_string()) {
+            return None;
+        }
+        Some(UploadLockGuard {
+            locks: self,
+            id: id.to_string(),
+        })
+    }
+}
+
+impl Drop for UploadLockGuard<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut active) = self.locks.0.lock() {
+            active.remove(&self.id);
+        }
+    }
+}
@@ -0,0 +1,417 @@
+use crate::FileInfo;
+use actix_web::HttpResponse;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+// Aturan upload per lomba yang diatur admin: kuota total per meja, jumlah
+// file maksimal, dan tipe file yang boleh diupload. Setiap versi file
+// dihitung, jadi peserta bisa menghapus versi lama untuk memberi ruang.
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct UploadRules {
+    pub max_total_bytes: Option<u64>,
+    pub max_files: Option<u32>,
+    // Ekstensi (".py") atau MIME ("application/zip", "text/*"); kosong berarti semua tipe boleh
+    #[serde(default)]
+    pub allowed_types: Vec<String>,
+}
+
+// Jumlah byte awal file yang dibaca untuk deteksi tipe isi
+pub const SNIFF_BYTES: usize = 16 * 1024;
+
+// Pemakaian meja saat ini
+#[derive(Clone, Copy, Default)]
+pub struct MejaUsage {
+    pub files: usize,
+    pub bytes: u64,
+}
+
+impl MejaUsage {
+    pub fn of(files: &[FileInfo]) -> Self {
+        MejaUsage {
+            files: files.len(),
+            bytes: files.iter().map(|f| f.size).sum(),
+        }
+    }
+
+    pub fn add(&mut self, file: &FileInfo) {
+        self.files += 1;
+        self.bytes += file.size;
+    }
+
+    // Pemakaian ditambah ruang yang dipesan sesi upload bertahap
+    pub fn with_reserved(self, reserved: MejaUsage) -> Self {
+        MejaUsage {
+            files: self.files + reserved.files,
+            bytes: self.bytes.saturating_add(reserved.bytes),
+        }
+    }
+
+    // Pemakaian tanpa `file`, untuk file yang akan diganti
+    pub fn without(self, file: &FileInfo) -> Self {
+        MejaUsage {
+            files: self.files.saturating_sub(1),
+            bytes: self.bytes.saturating_sub(file.size),
+        }
+    }
+}
+
+pub enum RuleViolation {
+    TooManyFiles { max_files: u32 },
+    QuotaExceeded { max_total_bytes: u64 },
+    TypeNotAllowed { allowed_types: Vec<String>, detected: Option<String> },
+}
+
+impl RuleViolation {
+    pub fn code(&self) -> &'static str {
+        match self {
+            RuleViolation::TooManyFiles { .. } => "too_many_files",
+            RuleViolation::QuotaExceeded { .. } => "quota_exceeded",
+            RuleViolation::TypeNotAllowed { .. } => "type_not_allowed",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            RuleViolation::TooManyFiles { max_files } => {
+                format!("Jumlah file sudah mencapai batas {} file, hapus file lama terlebih dahulu", max_files)
+            }
+            RuleViolation::QuotaExceeded { max_total_bytes } => {
+                format!("Total ukuran file melebihi kuota meja {}", format_bytes(*max_total_bytes))
+            }
+            RuleViolation::TypeNotAllowed { allowed_types, detected: Some(detected) } => format!(
+                "Isi file terdeteksi sebagai {} dan tidak diizinkan. Tipe yang diizinkan: {}",
+                detected,
+                allowed_types.join(", ")
+            ),
+            RuleViolation::TypeNotAllowed { allowed_types, detected: None } => {
+                format!("Tipe file tidak diizinkan. Tipe yang diizinkan: {}", allowed_types.join(", "))
+            }
+        }
+    }
+
+    // `error` dan `code` ditambah batas yang dilanggar, supaya halaman peserta
+    // bisa menampilkan detailnya
+    pub fn body(&self) -> serde_json::Value {
+        let mut body = serde_json::json!({"error": self.message(), "code": self.code()});
+        match self {
+            RuleViolation::TooManyFiles { max_files } => body["max_files"] = (*max_files).into(),
+            RuleViolation::QuotaExceeded { max_total_bytes } => body["max_total_bytes"] = (*max_total_bytes).into(),
+            RuleViolation::TypeNotAllowed { allowed_types, detected } => {
+                body["allowed_types"] = allowed_types.clone().into();
+                body["detected_type"] = detected.clone().into();
+            }
+        }
+        body
+    }
+
+    pub fn status(&self) -> actix_web::HttpResponseBuilder {
+        match self {
+            RuleViolation::TooManyFiles { .. } => HttpResponse::Conflict(),
+            RuleViolation::QuotaExceeded { .. } => HttpResponse::PayloadTooLarge(),
+            RuleViolation::TypeNotAllowed { .. } => HttpResponse::UnsupportedMediaType(),
+        }
+    }
+
+    pub fn response(&self) -> HttpResponse {
+        self.status().json(self.body())
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    if bytes < 1024 * 1024 {
+        format!("{}KB", bytes.div_ceil(1024))
+    } else {
+        format!("{:.1}MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+// === Checks ===
+
+impl UploadRules {
+    // Normalisasi input admin: huruf kecil, ekstensi selalu diawali titik,
+    // entri boleh dipisah koma
+    pub fn normalize(mut self) -> Result<Self, String> {
+        // Kuota disimpan sebagai INTEGER SQLite yang bertanda
+        if self.max_total_bytes.is_some_and(|b| i64::try_from(b).is_err()) {
+            return Err("max_total_bytes terlalu besar".to_string());
+        }
+        let mut allowed_types = vec![];
+        for entry in self.allowed_types.iter().flat_map(|t| t.split(',')) {
+            let entry = entry.trim().to_lowercase();
+            if entry.is_empty() {
+                continue;
+            }
+            let entry = if entry.contains('/') {
+                match entry.split_once('/') {
+                    Some((kind, sub)) if !kind.is_empty() && !sub.is_empty() && !sub.contains('/') => entry,
+                    _ => return Err(format!("Tipe MIME tidak valid: {}", entry)),
+                }
+            } else {
+                format!(".{}", entry.trim_start_matches('.'))
+            };
+            if !allowed_types.contains(&entry) {
+                allowed_types.push(entry);
+            }
+        }
+        self.allowed_types = allowed_types;
+        Ok(self)
+    }
+
+    // Cek yang bisa dilakukan sebelum isi file dibaca
+    pub fn check_new_file(&self, usage: MejaUsage, filename: &str) -> Result<(), RuleViolation> {
+        if let Some(max_files) = self.max_files {
+            if usage.files >= max_files as usize {
+                return Err(RuleViolation::TooManyFiles { max_files });
+            }
+        }
+        if let Some(max_total_bytes) = self.max_total_bytes {
+            if usage.bytes >= max_total_bytes {
+                return Err(RuleViolation::QuotaExceeded { max_total_bytes });
+            }
+        }
+        // Tanpa aturan MIME, nama file saja sudah cukup untuk menolak
+        let has_mime_rule = self.allowed_types.iter().any(|t| t.contains('/'));
+        if !self.allowed_types.is_empty() && !has_mime_rule && !self.extension_allowed(filename) {
+            return Err(self.type_violation(None));
+        }
+        Ok(())
+    }
+
+    pub fn check_size(&self, usage: MejaUsage, size: u64) -> Result<(), RuleViolation> {
+        match self.max_total_bytes {
+            Some(max_total_bytes) if usage.bytes.saturating_add(size) > max_total_bytes => {
+                Err(RuleViolation::QuotaExceeded { max_total_bytes })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // Batas ukuran satu file baru: batas per file atau sisa kuota meja, mana
+    // yang lebih kecil. Jika kuota yang berlaku, pelanggarannya ikut dikembalikan.
+    pub fn size_limit(&self, usage: MejaUsage, max_file_size: u64) -> (u64, Option<RuleViolation>) {
+        match self.max_total_bytes {
+            Some(max_total_bytes) if max_total_bytes.saturating_sub(usage.bytes) < max_file_size => (
+                max_total_bytes.saturating_sub(usage.bytes),
+                Some(RuleViolation::QuotaExceeded { max_total_bytes }),
+            ),
+            _ => (max_file_size, None),
+        }
+    }
+
+    // `head` adalah awal isi file. Ekstensi yang diizinkan hanya berlaku jika
+    // isinya tidak terdeteksi sebagai tipe lain (misal .exe yang diganti
+    // namanya menjadi .zip); aturan MIME dicocokkan dengan tipe hasil deteksi.
+    pub fn check_content(&self, filename: &str, head: &[u8]) -> Result<(), RuleViolation> {
+        if self.allowed_types.is_empty() {
+            return Ok(());
+        }
+        let kind = infer::get(head);
+        let detected = match kind {
+            Some(kind) => kind.mime_type(),
+            None if looks_like_text(head) => "text/plain",
+            None => "application/octet-stream",
+        };
+
+        let name_matches_content = match kind {
+            None => true,
+            Some(kind) => {
+                filename.to_lowercase().ends_with(kind.extension())
+                    || mime_guess::from_path(filename).iter().any(|m| m.essence_str() == kind.mime_type())
+            }
+        };
+        if name_matches_content && self.extension_allowed(filename) {
+            return Ok(());
+        }
+        if self.allowed_types.iter().any(|t| mime_matches(t, detected)) {
+            return Ok(());
+        }
+        Err(self.type_violation(Some(detected.to_string())))
+    }
+
+    fn extension_allowed(&self, filename: &str) -> bool {
+        let filename = filename.to_lowercase();
+        self.allowed_types
+            .iter()
+            .any(|t| t.starts_with('.') && filename.ends_with(t.as_str()))
+    }
+
+    fn type_violation(&self, detected: Option<String>) -> RuleViolation {
+        RuleViolation::TypeNotAllowed {
+            allowed_types: self.allowed_types.clone(),
+            detected,
+        }
+    }
+}
+
+fn mime_matches(pattern: &str, mime: &str) -> bool {
+    match pattern.strip_suffix("/*") {
+        Some(kind) => mime.split('/').next() == Some(kind),
+        None => pattern == mime,
+    }
+}
+
+// UTF-8 tanpa byte nol; karakter yang terpotong di akhir `head` masih dianggap teks
+fn looks_like_text(head: &[u8]) -> bool {
+    if head.contains(&0) {
+        return false;
+    }
+    match std::str::from_utf8(head) {
+        Ok(_) => true,
+        Err(e) => e.error_len().is_none(),
+    }
+}
+
+// === Database ===
+
+pub fn init_rules_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS upload_rules (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            max_total_bytes INTEGER,
+            max_files INTEGER,
+            allowed_types TEXT NOT NULL DEFAULT ''
+        )",
+        [],
+    )?;
+    conn.execute("INSERT OR IGNORE INTO upload_rules (id) VALUES (1)", [])?;
+    Ok(())
+}
+
+pub fn load_rules(conn: &Connection) -> UploadRules {
+    conn.query_row(
+        "SELECT max_total_bytes, max_files, allowed_types FROM upload_rules WHERE id = 1",
+        [],
+        |row| {
+            let allowed_types: String = row.get(2)?;
+            Ok(UploadRules {
+                max_total_bytes: row.get(0)?,
+                max_files: row.get(1)?,
+                allowed_types: allowed_types
+                    .split(',')
+                    .filter(|t| !t.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+            })
+        },
+    )
+    .unwrap_or_default()
+}
+
+pub fn save_rules(conn: &Connection, rules: &UploadRules) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE upload_rules SET max_total_bytes = ?1, max_files = ?2, allowed_types = ?3 WHERE id = 1",
+        params![
+            rules.max_total_bytes.map(|b| b as i64),
+            rules.max_files,
+            rules.allowed_types.join(",")
+        ],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn allowing(types: &[&str]) -> UploadRules {
+        UploadRules {
+            allowed_types: types.iter().map(|t| t.to_string()).collect(),
+            ..UploadRules::default()
+        }
+    }
+
+    fn zip_with(names: &[&str]) -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for name in names {
+            zip.start_file(*name, zip::write::SimpleFileOptions::default()).unwrap();
+            zip.write_all(b"isi").unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    fn detected(result: Result<(), RuleViolation>) -> Option<String> {
+        match result {
+            Err(RuleViolation::TypeNotAllowed { detected, .. }) => detected,
+            _ => panic!("seharusnya ditolak"),
+        }
+    }
+
+    #[test]
+    fn no_type_rule_accepts_anything() {
+        assert!(UploadRules::default().check_content("a.exe", b"MZ\x90\x00").is_ok());
+    }
+
+    #[test]
+    fn extension_needs_matching_content() {
+        let rules = allowing(&[".zip", ".py"]);
+        assert!(rules.check_content("tugas.zip", &zip_with(&["main.py"])).is_ok());
+        assert!(rules.check_content("main.py", b"print('halo')\n").is_ok());
+
+        // Isi yang terdeteksi sebagai tipe lain tidak lolos hanya karena namanya
+        let png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR";
+        assert_eq!(detected(rules.check_content("main.py", png)).as_deref(), Some("image/png"));
+        assert!(rules.check_content("tugas.zip", b"MZ\x90\x00\x03\x00").is_err());
+        assert!(rules.check_content("main.c", b"int main() {}").is_err());
+    }
+
+    #[test]
+    fn zip_based_formats_are_not_plain_zip() {
+        let rules = allowing(&[".zip"]);
+        let docx = zip_with(&["word/document.xml"]);
+        let docx_type = detected(rules.check_content("tugas.zip", &docx)).unwrap();
+        assert!(docx_type.contains("wordprocessingml"), "{}", docx_type);
+
+        // Nama yang sesuai isinya tetap harus diizinkan
+        assert!(rules.check_content("tugas.docx", &docx).is_err());
+        assert!(allowing(&[".docx"]).check_content("tugas.docx", &docx).is_ok());
+
+        // Jar tidak dikenali infer, jadi diperlakukan sebagai zip biasa
+        let jar = zip_with(&["META-INF/MANIFEST.MF"]);
+        assert!(rules.check_content("tugas.zip", &jar).is_ok());
+        assert!(rules.check_content("tugas.jar", &jar).is_err());
+    }
+
+    #[test]
+    fn mime_rule_matches_detected_type() {
+        let pdf = b"%PDF-1.4\n";
+        assert!(allowing(&["application/pdf"]).check_content("laporan.bin", pdf).is_ok());
+        assert!(allowing(&["image/*"]).check_content("gambar.dat", b"\x89PNG\r\n\x1a\n").is_ok());
+        assert!(allowing(&["image/*"]).check_content("laporan.png", pdf).is_err());
+    }
+
+    #[test]
+    fn text_detection() {
+        let rules = allowing(&["text/plain"]);
+        assert!(rules.check_content("catatan", "halo dunia".as_bytes()).is_ok());
+        // Karakter UTF-8 yang terpotong di akhir head masih teks
+        assert!(rules.check_content("catatan", &"héllo".as_bytes()[..2]).is_ok());
+        assert_eq!(
+            detected(rules.check_content("catatan", b"ab\x00cd")).as_deref(),
+            Some("application/octet-stream")
+        );
+        assert!(rules.check_content("catatan", b"\xff\xfe\xfd abc").is_err());
+    }
+
+    #[test]
+    fn normalize_splits_and_cleans_types() {
+        let rules = allowing(&[" .PY, zip ,", "Application/ZIP", ".py", "text/*"]).normalize().unwrap();
+        assert_eq!(rules.allowed_types, vec![".py", ".zip", "application/zip", "text/*"]);
+
+        assert!(allowing(&["text/"]).normalize().is_err());
+        assert!(allowing(&["/plain"]).normalize().is_err());
+        assert!(allowing(&["a/b/c"]).normalize().is_err());
+    }
+
+    #[test]
+    fn normalize_rejects_quota_above_i64() {
+        let quota = |bytes: u64| UploadRules {
+            max_total_bytes: Some(bytes),
+            ..UploadRules::default()
+        };
+        assert!(quota(i64::MAX as u64).normalize().is_ok());
+        assert!(quota(i64::MAX as u64 + 1).normalize().is_err());
+        assert!(quota(u64::MAX).normalize().is_err());
+    }
+}
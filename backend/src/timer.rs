@@ -0,0 +1,666 @@
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+// Timer lomba. Sumber kebenarannya adalah `deadline`: saat timer mencapai nol
+// jika terus berjalan. Jeda menggeser deadline sebesar lama jedanya (dicatat
+// di `paused_seconds`) dan adjust menggeser deadline langsung, jadi sisa waktu
+// selalu dihitung ulang dari deadline dan tidak melompat setelah jeda atau
+// server restart.
+//
+// Fase timer dari kolom yang terisi:
+// - belum dimulai: `deadline` kosong, sisa waktu ada di `remaining_seconds`
+// - berjalan:      `is_running`, `deadline` terisi
+// - dijeda:        `deadline` dan `paused_at` terisi
+// - habis:         `deadline` terisi tanpa `paused_at`, `ended_at` = deadline
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TimerState {
+    pub is_running: bool,
+    // Durasi yang diatur admin; adjust tidak mengubahnya
+    pub duration_seconds: i64,
+    // Dihitung dari deadline, kecuali sebelum timer dimulai
+    pub remaining_seconds: i64,
+    // Pertama kali timer dimulai sejak diatur/direset
+    pub started_at: Option<DateTime<Utc>>,
+    pub deadline: Option<DateTime<Utc>>,
+    // Awal jeda yang sedang berlangsung
+    pub paused_at: Option<DateTime<Utc>>,
+    // Total jeda yang sudah selesai, dibulatkan per detik
+    pub paused_seconds: i64,
+    // Kapan timer mencapai nol; dasar perhitungan keterlambatan upload
+    pub ended_at: Option<DateTime<Utc>>,
+    // Setelah timer habis, upload masih diterima selama ini tapi ditandai terlambat
+    pub grace_seconds: i64,
+}
+
+// Batas durasi, masa tenggang, dan besar satu kali adjust dari admin
+pub const MAX_DURATION_SECONDS: i64 = 7 * 24 * 60 * 60;
+
+// Detik dari `from` ke `to`, dibulatkan ke atas; 0 jika sudah lewat
+fn seconds_between(from: DateTime<Utc>, to: DateTime<Utc>) -> i64 {
+    let ms = to.signed_duration_since(from).num_milliseconds();
+    if ms > 0 {
+        (ms + 999) / 1000
+    } else {
+        0
+    }
+}
+
+impl TimerState {
+    pub fn new(duration_seconds: i64) -> Self {
+        TimerState {
+            is_running: false,
+            duration_seconds,
+            remaining_seconds: duration_seconds,
+            started_at: None,
+            deadline: None,
+            paused_at: None,
+            paused_seconds: 0,
+            ended_at: None,
+            grace_seconds: 0,
+        }
+    }
+
+    pub fn set_duration(&mut self, duration_seconds: i64) {
+        self.duration_seconds = duration_seconds;
+        self.reset();
+    }
+
+    // Kembali ke awal; masa tenggang tetap
+    pub fn reset(&mut self) {
+        *self = TimerState {
+            grace_seconds: self.grace_seconds,
+            ..TimerState::new(self.duration_seconds)
+        };
+    }
+
+    // Mulai atau lanjutkan setelah jeda. false jika tidak ada yang berubah.
+    pub fn start(&mut self, now: DateTime<Utc>) -> bool {
+        if self.is_running {
+            return false;
+        }
+        match (self.deadline, self.paused_at) {
+            (None, _) if self.remaining_seconds > 0 => {
+                let Some(deadline) = add_seconds(now, self.remaining_seconds) else {
+                    return false;
+                };
+                self.deadline = Some(deadline);
+                self.started_at = Some(now);
+            }
+            (Some(deadline), Some(paused_at)) => {
+                let paused = now.signed_duration_since(paused_at).max(Duration::zero());
+                self.deadline = Some(deadline + paused);
+                self.paused_seconds += (paused.num_milliseconds() + 500) / 1000;
+                self.paused_at = None;
+            }
+            _ => return false,
+        }
+        self.is_running = true;
+        self.sync(now);
+        true
+    }
+
+    pub fn pause(&mut self, now: DateTime<Utc>) -> bool {
+        if !self.is_running {
+            return false;
+        }
+        self.is_running = false;
+        // Deadline yang sudah lewat berarti timer habis, bukan dijeda
+        if self.deadline.is_some_and(|deadline| deadline > now) {
+            self.paused_at = Some(now);
+        }
+        self.sync(now);
+        true
+    }
+
+    // Tambah/kurangi sisa waktu. Sisa yang menjadi nol menghabiskan timer saat
+    // itu juga; tambahan pada timer yang sudah habis membuatnya dijeda.
+    // Pergeseran di luar rentang waktu chrono diabaikan jika menambah, dan
+    // menghabiskan timer jika mengurangi.
+    pub fn adjust(&mut self, seconds: i64, now: DateTime<Utc>) {
+        match (self.deadline, self.paused_at) {
+            (None, _) => {
+                self.remaining_seconds = self.remaining_seconds.saturating_add(seconds).max(0);
+                if self.remaining_seconds == 0 && self.duration_seconds > 0 {
+                    self.deadline = Some(now);
+                }
+            }
+            (Some(deadline), Some(paused_at)) => match add_seconds(deadline, seconds) {
+                Some(shifted) if shifted > paused_at => self.deadline = Some(shifted),
+                None if seconds > 0 => {}
+                _ => {
+                    self.deadline = Some(now);
+                    self.paused_at = None;
+                }
+            },
+            (Some(deadline), None) if self.is_running => match add_seconds(deadline, seconds) {
+                Some(shifted) => self.deadline = Some(shifted.max(now)),
+                None if seconds > 0 => {}
+                None => self.deadline = Some(now),
+            },
+            (Some(_), None) => {
+                if let Some(deadline) = add_seconds(now, seconds).filter(|_| seconds > 0) {
+                    self.deadline = Some(deadline);
+                    self.paused_at = Some(now);
+                }
+            }
+        }
+        self.sync(now);
+    }
+
+    // Hitung ulang sisa waktu dan `ended_at`. true jika timer baru saja habis
+    // (perlu disimpan ke database).
+    pub fn sync(&mut self, now: DateTime<Utc>) -> bool {
+        self.remaining_seconds = match (self.deadline, self.paused_at) {
+            (None, _) => self.remaining_seconds,
+            (Some(deadline), Some(paused_at)) => seconds_between(paused_at, deadline),
+            (Some(deadline), None) => seconds_between(now, deadline),
+        };
+        let just_ended = self.is_running && self.remaining_seconds == 0;
+        if just_ended {
+            self.is_running = false;
+        }
+        self.ended_at = if self.is_running { None } else { self.end_time() };
+        just_ended
+    }
+
+    // Akhir timer global: deadline selama berjalan atau setelah habis. None jika
+    // dijeda, belum dimulai, atau lomba tanpa timer.
+    fn end_time(&self) -> Option<DateTime<Utc>> {
+        match (self.deadline, self.paused_at) {
+            (Some(deadline), None) => Some(deadline),
+            _ => None,
+        }
+    }
+
+    // Deadline satu meja dengan tambahan waktu `extension_seconds`
+    pub fn deadline(&self, extension_seconds: i64) -> Option<DateTime<Utc>> {
+        let end = self.end_time()?;
+        Some(add_seconds(end, extension_seconds).unwrap_or(end))
+    }
+
+    // Kapan deadline meja lewat; None jika belum lewat
+    pub fn ended_at(&self, now: DateTime<Utc>, extension_seconds: i64) -> Option<DateTime<Utc>> {
+        self.deadline(extension_seconds).filter(|deadline| *deadline <= now)
+    }
+
+    // Keterlambatan upload yang diterima pada `at`, dibulatkan ke atas per detik;
+    // None jika tepat waktu
+    pub fn seconds_late(&self, at: DateTime<Utc>, extension_seconds: i64) -> Option<i64> {
+        let late_ms = at
+            .signed_duration_since(self.ended_at(at, extension_seconds)?)
+            .num_milliseconds();
+        (late_ms > 0).then(|| (late_ms + 999) / 1000)
+    }
+
+    // Sebelum deadline meja atau masih dalam masa tenggang
+    pub fn accepts_upload(&self, at: DateTime<Utc>, extension_seconds: i64) -> bool {
+        match self.ended_at(at, extension_seconds) {
+            Some(end) => at.signed_duration_since(end).num_milliseconds() <= self.grace_seconds * 1000,
+            None => true,
+        }
+    }
+
+    // Timer seperti yang dialami satu meja: durasi, sisa waktu, dan deadline
+    // ditambah tambahan waktunya. Setelah timer global habis, meja yang masih
+    // punya tambahan waktu melihat timer yang tetap berjalan sampai deadline-nya.
+    pub fn for_meja(&self, extension_seconds: i64, now: DateTime<Utc>) -> TimerState {
+        let mut timer = self.clone();
+        if extension_seconds == 0 || (self.deadline.is_none() && self.remaining_seconds <= 0) {
+            return timer;
+        }
+        timer.duration_seconds = timer.duration_seconds.saturating_add(extension_seconds);
+        match timer.deadline {
+            Some(deadline) => timer.deadline = Some(add_seconds(deadline, extension_seconds).unwrap_or(deadline)),
+            None => timer.remaining_seconds = timer.remaining_seconds.saturating_add(extension_seconds),
+        }
+        if timer.paused_at.is_none() && timer.deadline.is_some_and(|deadline| deadline > now) {
+            timer.is_running = true;
+        }
+        timer.sync(now);
+        timer
+    }
+}
+
+// `at` digeser `seconds` detik; None jika di luar rentang waktu chrono
+fn add_seconds(at: DateTime<Utc>, seconds: i64) -> Option<DateTime<Utc>> {
+    Duration::try_seconds(seconds).and_then(|delta| at.checked_add_signed(delta))
+}
+
+// === Database ===
+
+pub fn init_timer_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS timer (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            is_running INTEGER NOT NULL DEFAULT 0,
+            duration_seconds INTEGER NOT NULL DEFAULT 3600,
+            remaining_seconds INTEGER NOT NULL DEFAULT 3600,
+            started_at TEXT
+        )",
+        [],
+    )?;
+    crate::add_column_if_missing(conn, "timer", "ended_at", "TEXT")?;
+    crate::add_column_if_missing(conn, "timer", "grace_seconds", "INTEGER NOT NULL DEFAULT 0")?;
+    crate::add_column_if_missing(conn, "timer", "deadline", "TEXT")?;
+    crate::add_column_if_missing(conn, "timer", "paused_at", "TEXT")?;
+    crate::add_column_if_missing(conn, "timer", "paused_seconds", "INTEGER NOT NULL DEFAULT 0")?;
+
+    conn.execute(
+        "INSERT OR IGNORE INTO timer (id, is_running, duration_seconds, remaining_seconds) VALUES (1, 0, 3600, 3600)",
+        [],
+    )?;
+    Ok(())
+}
+
+// Timer tersimpan, dihitung ulang pada `now`. Timer yang habis selama server
+// mati langsung dicatat habis pada deadline-nya.
+pub fn load_timer(conn: &Connection, now: DateTime<Utc>) -> TimerState {
+    let parse_time = |value: Option<String>| {
+        value.and_then(|s| {
+            DateTime::parse_from_rfc3339(&s)
+                .map(|dt| dt.with_timezone(&Utc))
+                .ok()
+        })
+    };
+    let timer = conn.query_row(
+        "SELECT is_running, duration_seconds, remaining_seconds, started_at, deadline, paused_at, paused_seconds, ended_at, grace_seconds FROM timer WHERE id = 1",
+        [],
+        |row| {
+            Ok(TimerState {
+                is_running: row.get::<_, i32>(0)? != 0,
+                duration_seconds: row.get(1)?,
+                remaining_seconds: row.get(2)?,
+                started_at: parse_time(row.get(3)?),
+                deadline: parse_time(row.get(4)?),
+                paused_at: parse_time(row.get(5)?),
+                paused_seconds: row.get(6)?,
+                ended_at: parse_time(row.get(7)?),
+                grace_seconds: row.get(8)?,
+            })
+        },
+    );
+    let mut timer = match timer {
+        Ok(timer) => timer,
+        Err(_) => return TimerState::new(3600),
+    };
+
+    // Baris dari versi lama belum punya deadline: timer berjalan berakhir di
+    // started_at + durasi, timer habis pada ended_at
+    if timer.deadline.is_none() {
+        if timer.is_running {
+            timer.deadline = timer
+                .started_at
+                .map(|started| started + Duration::seconds(timer.duration_seconds));
+            timer.is_running = timer.deadline.is_some();
+        } else if timer.remaining_seconds <= 0 && timer.duration_seconds > 0 {
+            timer.deadline = Some(timer.ended_at.unwrap_or(DateTime::UNIX_EPOCH));
+        }
+    }
+
+    if timer.sync(now) {
+        save_timer_to_db(conn, &timer);
+    }
+    timer
+}
+
+pub fn save_timer_to_db(conn: &Connection, timer: &TimerState) {
+    let format_time = |value: Option<DateTime<Utc>>| value.map(|dt| dt.to_rfc3339());
+    conn.execute(
+        "UPDATE timer SET is_running = ?1, duration_seconds = ?2, remaining_seconds = ?3, started_at = ?4, deadline = ?5, paused_at = ?6, paused_seconds = ?7, ended_at = ?8, grace_seconds = ?9 WHERE id = 1",
+        params![
+            timer.is_running as i32,
+            timer.duration_seconds,
+            timer.remaining_seconds,
+            format_time(timer.started_at),
+            format_time(timer.deadline),
+            format_time(timer.paused_at),
+            timer.paused_seconds,
+            format_time(timer.ended_at),
+            timer.grace_seconds
+        ],
+    ).ok();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2026-01-01T08:00:00Z").unwrap().with_timezone(&Utc) + Duration::seconds(seconds)
+    }
+
+    fn started(duration_seconds: i64) -> TimerState {
+        let mut timer = TimerState::new(duration_seconds);
+        assert!(timer.start(at(0)));
+        timer
+    }
+
+    // Simulasi server restart: simpan, lalu muat dari database baru pada `now`
+    fn restart(conn: &Connection, timer: &TimerState, now: DateTime<Utc>) -> TimerState {
+        save_timer_to_db(conn, timer);
+        load_timer(conn, now)
+    }
+
+    fn db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_timer_tables(&conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn start_sets_deadline_from_remaining() {
+        let mut timer = started(600);
+        assert_eq!(timer.deadline, Some(at(600)));
+        assert_eq!(timer.remaining_seconds, 600);
+
+        timer.sync(at(250));
+        assert_eq!(timer.remaining_seconds, 350);
+        assert!(timer.is_running);
+    }
+
+    #[test]
+    fn pause_and_resume_keep_remaining_time() {
+        let mut timer = started(600);
+        assert!(timer.pause(at(100)));
+        assert_eq!(timer.remaining_seconds, 500);
+
+        // Selama dijeda sisa waktu tidak berkurang
+        timer.sync(at(350));
+        assert_eq!(timer.remaining_seconds, 500);
+
+        assert!(timer.start(at(400)));
+        assert_eq!(timer.deadline, Some(at(900)));
+        assert_eq!(timer.paused_seconds, 300);
+        assert_eq!(timer.remaining_seconds, 500);
+        assert_eq!(timer.started_at, Some(at(0)));
+
+        timer.sync(at(450));
+        assert_eq!(timer.remaining_seconds, 450);
+    }
+
+    #[test]
+    fn repeated_pauses_accumulate_in_ledger() {
+        let mut timer = started(600);
+        timer.pause(at(100));
+        timer.start(at(160));
+        timer.pause(at(200));
+        timer.start(at(230));
+        assert_eq!(timer.paused_seconds, 90);
+        assert_eq!(timer.deadline, Some(at(690)));
+
+        timer.sync(at(630));
+        assert_eq!(timer.remaining_seconds, 60);
+    }
+
+    #[test]
+    fn start_and_pause_are_idempotent() {
+        let mut timer = started(600);
+        assert!(!timer.start(at(50)));
+        assert_eq!(timer.deadline, Some(at(600)));
+
+        timer.pause(at(100));
+        assert!(!timer.pause(at(150)));
+        assert_eq!(timer.paused_at, Some(at(100)));
+    }
+
+    #[test]
+    fn timer_ends_at_deadline() {
+        let mut timer = started(60);
+        assert!(!timer.sync(at(59)));
+        assert!(timer.sync(at(61)));
+        assert!(!timer.is_running);
+        assert_eq!(timer.remaining_seconds, 0);
+        assert_eq!(timer.ended_at, Some(at(60)));
+        assert!(!timer.start(at(70)));
+    }
+
+    #[test]
+    fn adjust_running_timer_moves_deadline_only() {
+        let mut timer = started(600);
+        timer.adjust(120, at(100));
+        assert_eq!(timer.deadline, Some(at(720)));
+        assert_eq!(timer.remaining_seconds, 620);
+        assert_eq!(timer.duration_seconds, 600);
+
+        timer.adjust(-300, at(200));
+        assert_eq!(timer.deadline, Some(at(420)));
+        assert_eq!(timer.remaining_seconds, 220);
+    }
+
+    #[test]
+    fn adjust_below_zero_ends_running_timer_now() {
+        let mut timer = started(600);
+        timer.adjust(-1000, at(100));
+        assert!(!timer.is_running);
+        assert_eq!(timer.remaining_seconds, 0);
+        assert_eq!(timer.ended_at, Some(at(100)));
+    }
+
+    #[test]
+    fn adjust_paused_timer() {
+        let mut timer = started(600);
+        timer.pause(at(100));
+        timer.adjust(60, at(200));
+        assert_eq!(timer.remaining_seconds, 560);
+
+        timer.start(at(300));
+        assert_eq!(timer.deadline, Some(at(860)));
+
+        timer.pause(at(400));
+        timer.adjust(-1000, at(450));
+        assert_eq!(timer.remaining_seconds, 0);
+        assert_eq!(timer.paused_at, None);
+        assert_eq!(timer.ended_at, Some(at(450)));
+        // Durasi tetap; durasi nol berarti lomba tanpa timer
+        assert_eq!(timer.duration_seconds, 600);
+        assert_eq!(timer.deadline(0), Some(at(450)));
+    }
+
+    #[test]
+    fn adjust_before_start_changes_initial_remaining() {
+        let mut timer = TimerState::new(600);
+        timer.adjust(-100, at(0));
+        assert_eq!(timer.remaining_seconds, 500);
+        assert_eq!(timer.deadline, None);
+
+        timer.start(at(10));
+        assert_eq!(timer.deadline, Some(at(510)));
+    }
+
+    #[test]
+    fn adjust_after_end_reopens_as_paused() {
+        let mut timer = started(60);
+        timer.sync(at(100));
+        timer.adjust(30, at(120));
+        assert!(!timer.is_running);
+        assert_eq!(timer.remaining_seconds, 30);
+        assert_eq!(timer.ended_at, None);
+        assert!(timer.accepts_upload(at(500), 0));
+
+        timer.start(at(200));
+        assert_eq!(timer.deadline, Some(at(230)));
+    }
+
+    #[test]
+    fn reset_keeps_grace_period() {
+        let mut timer = started(600);
+        timer.grace_seconds = 120;
+        timer.pause(at(100));
+        timer.reset();
+        assert_eq!(timer, TimerState { grace_seconds: 120, ..TimerState::new(600) });
+    }
+
+    #[test]
+    fn restart_while_running_restores_deadline() {
+        let conn = db();
+        let mut timer = started(600);
+        timer.pause(at(100));
+        timer.start(at(200));
+        timer.sync(at(300));
+
+        let restored = restart(&conn, &timer, at(450));
+        assert!(restored.is_running);
+        assert_eq!(restored.deadline, Some(at(700)));
+        assert_eq!(restored.paused_seconds, 100);
+        assert_eq!(restored.remaining_seconds, 250);
+        assert_eq!(restored.started_at, Some(at(0)));
+    }
+
+    #[test]
+    fn restart_while_paused_keeps_remaining() {
+        let conn = db();
+        let mut timer = started(600);
+        timer.pause(at(100));
+
+        let mut restored = restart(&conn, &timer, at(5000));
+        assert_eq!(restored, timer);
+        assert_eq!(restored.remaining_seconds, 500);
+
+        restored.start(at(6000));
+        assert_eq!(restored.deadline, Some(at(6500)));
+        assert_eq!(restored.paused_seconds, 5900);
+    }
+
+    #[test]
+    fn restart_after_deadline_ends_at_deadline() {
+        let conn = db();
+        let mut timer = started(600);
+        timer.grace_seconds = 60;
+
+        let restored = restart(&conn, &timer, at(1000));
+        assert!(!restored.is_running);
+        assert_eq!(restored.ended_at, Some(at(600)));
+        assert!(!restored.accepts_upload(at(1000), 0));
+
+        // Status habis ikut tersimpan
+        assert_eq!(load_timer(&conn, at(2000)), restored);
+    }
+
+    #[test]
+    fn restart_before_start_and_after_end() {
+        let conn = db();
+        let mut timer = TimerState::new(900);
+        timer.adjust(60, at(0));
+        assert_eq!(restart(&conn, &timer, at(100)), timer);
+
+        let mut timer = started(60);
+        timer.sync(at(60));
+        assert_eq!(restart(&conn, &timer, at(100)), timer);
+    }
+
+    #[test]
+    fn legacy_running_row_gets_deadline() {
+        let conn = db();
+        conn.execute(
+            "UPDATE timer SET is_running = 1, duration_seconds = 600, remaining_seconds = 600, started_at = ?1 WHERE id = 1",
+            params![at(0).to_rfc3339()],
+        )
+        .unwrap();
+
+        let timer = load_timer(&conn, at(100));
+        assert!(timer.is_running);
+        assert_eq!(timer.deadline, Some(at(600)));
+        assert_eq!(timer.remaining_seconds, 500);
+    }
+
+    #[test]
+    fn grace_and_lateness_follow_deadline() {
+        let mut timer = started(60);
+        timer.grace_seconds = 30;
+        timer.sync(at(70));
+
+        assert!(timer.accepts_upload(at(59), 0));
+        assert_eq!(timer.seconds_late(at(59), 0), None);
+        assert!(timer.accepts_upload(at(90), 0));
+        assert_eq!(timer.seconds_late(at(75), 0), Some(15));
+        assert!(!timer.accepts_upload(at(91), 0));
+
+        // Tambahan waktu meja menggeser deadline-nya
+        assert!(timer.accepts_upload(at(150), 120));
+        assert_eq!(timer.seconds_late(at(150), 120), None);
+        assert_eq!(timer.deadline(120), Some(at(180)));
+    }
+
+    #[test]
+    fn paused_timer_has_no_deadline() {
+        let mut timer = started(60);
+        timer.pause(at(30));
+        assert_eq!(timer.deadline(0), None);
+        assert!(timer.accepts_upload(at(1000), 0));
+    }
+
+    #[test]
+    fn meja_extension_keeps_timer_running_after_global_end() {
+        let mut timer = started(60);
+        timer.sync(at(100));
+
+        let meja = timer.for_meja(120, at(100));
+        assert!(meja.is_running);
+        assert_eq!(meja.deadline, Some(at(180)));
+        assert_eq!(meja.remaining_seconds, 80);
+        assert_eq!(meja.ended_at, None);
+
+        let meja = timer.for_meja(120, at(200));
+        assert!(!meja.is_running);
+        assert_eq!(meja.ended_at, Some(at(180)));
+
+        // Sebelum dimulai dan saat dijeda hanya sisa waktunya yang bertambah
+        let idle = TimerState::new(600).for_meja(60, at(0));
+        assert_eq!((idle.is_running, idle.remaining_seconds), (false, 660));
+        let mut paused = started(600);
+        paused.pause(at(100));
+        let paused = paused.for_meja(60, at(200));
+        assert_eq!((paused.is_running, paused.remaining_seconds), (false, 560));
+    }
+
+    #[test]
+    fn huge_extension_does_not_panic() {
+        let mut timer = started(60);
+        timer.sync(at(100));
+        assert_eq!(timer.deadline(i64::MAX), Some(at(60)));
+        assert!(!timer.accepts_upload(at(100), i64::MAX));
+
+        let meja = timer.for_meja(i64::MAX, at(100));
+        assert_eq!(meja.deadline, Some(at(60)));
+        let idle = TimerState::new(600).for_meja(i64::MAX, at(0));
+        assert_eq!(idle.remaining_seconds, i64::MAX);
+    }
+
+    #[test]
+    fn out_of_range_input_does_not_panic() {
+        // Sisa waktu di luar rentang chrono tidak bisa dimulai
+        let mut timer = TimerState::new(i64::MAX);
+        assert!(!timer.start(at(0)));
+        assert_eq!(timer.deadline, None);
+
+        let mut idle = TimerState::new(60);
+        idle.adjust(i64::MAX, at(0));
+        assert_eq!(idle.remaining_seconds, i64::MAX);
+        idle.adjust(i64::MIN, at(0));
+        assert_eq!(idle.remaining_seconds, 0);
+
+        let mut running = started(60);
+        running.adjust(i64::MAX, at(10));
+        assert_eq!(running.deadline, Some(at(60)));
+        running.adjust(MAX_DURATION_SECONDS, at(10));
+        assert_eq!(running.deadline, Some(at(60 + MAX_DURATION_SECONDS)));
+        running.adjust(i64::MIN, at(10));
+        assert_eq!((running.is_running, running.ended_at), (false, Some(at(10))));
+
+        let mut paused = started(60);
+        paused.pause(at(10));
+        paused.adjust(i64::MAX, at(20));
+        assert_eq!(paused.remaining_seconds, 50);
+        paused.adjust(i64::MIN, at(20));
+        assert_eq!((paused.paused_at, paused.ended_at), (None, Some(at(20))));
+
+        let mut ended = started(60);
+        ended.sync(at(100));
+        ended.adjust(i64::MAX, at(100));
+        assert_eq!(ended.paused_at, None);
+        ended.adjust(MAX_DURATION_SECONDS, at(100));
+        assert_eq!(ended.remaining_seconds, MAX_DURATION_SECONDS);
+    }
+}
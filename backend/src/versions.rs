@@ -0,0 +1,80 @@
+use crate::FileInfo;
+use rusqlite::{params, Connection};
+use similar::TextDiff;
+use std::collections::BTreeMap;
+
+// Setiap upload disimpan sebagai blob terpisah. Upload dengan nama file sama
+// di satu meja menjadi versi berikutnya (v1, v2, ...). Peserta bisa menandai
+// satu versi sebagai final; jika tidak ada, versi terbaru yang dinilai.
+
+// === Database ===
+
+pub fn next_version(conn: &Connection, meja_id: &str, filename: &str) -> rusqlite::Result<u32> {
+    conn.query_row(
+        "SELECT COALESCE(MAX(version), 0) + 1 FROM files WHERE meja_id = ?1 AND filename = ?2",
+        params![meja_id, filename],
+        |row| row.get(0),
+    )
+}
+
+// Database lama menyimpan semua upload sebagai v1; urutkan ulang dari waktu upload
+pub fn renumber_versions(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE files SET version = (
+            SELECT COUNT(*) FROM files f2
+            WHERE f2.meja_id = files.meja_id AND f2.filename = files.filename
+              AND (f2.uploaded_at < files.uploaded_at OR (f2.uploaded_at = files.uploaded_at AND f2.id <= files.id))
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+pub fn mark_final(conn: &Connection, meja_id: &str, file: &FileInfo) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE files SET is_final = (id = ?1) WHERE meja_id = ?2 AND filename = ?3",
+        params![file.id, meja_id, file.filename],
+    )?;
+    Ok(())
+}
+
+// === Grouping ===
+
+// Versi per nama file, diurutkan dari v1
+pub fn by_filename(files: &[FileInfo]) -> BTreeMap<&str, Vec<&FileInfo>> {
+    let mut groups: BTreeMap<&str, Vec<&FileInfo>> = BTreeMap::new();
+    for file in files {
+        groups.entry(file.filename.as_str()).or_default().push(file);
+    }
+    for versions in groups.values_mut() {
+        versions.sort_by_key(|f| (f.version, f.uploaded_at));
+    }
+    groups
+}
+
+// Versi yang dinilai: yang ditandai final, atau versi terakhir
+pub fn submitted<'a>(versions: &[&'a FileInfo]) -> Option<&'a FileInfo> {
+    versions
+        .iter()
+        .find(|f| f.is_final)
+        .or_else(|| versions.last())
+        .copied()
+}
+
+// Versi tepat sebelum `file` dengan nama yang sama
+pub fn previous<'a>(files: &'a [FileInfo], file: &FileInfo) -> Option<&'a FileInfo> {
+    files
+        .iter()
+        .filter(|f| f.filename == file.filename && f.version < file.version)
+        .max_by_key(|f| f.version)
+}
+
+// === Diff ===
+
+pub fn unified_diff(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    TextDiff::from_lines(old, new)
+        .unified_diff()
+        .context_radius(3)
+        .header(old_label, new_label)
+        .to_string()
+}
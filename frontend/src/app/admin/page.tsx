@@ -6,7 +6,15 @@ import { api } from "@/lib/api";
 import type { FileInfo } from "@/lib/types";
 import { Timer } from "@/components/Timer";
 import { ArchiveViewer, FileViewer } from "@/components/ArchiveViewer";
+import { DiffViewer } from "@/components/DiffViewer";
+import { isSubmittedVersion, sortByVersion } from "@/lib/versions";
+import { formatSecondsLate } from "@/lib/lateness";
 import { AdminLogin } from "@/components/AdminLogin";
+import { LoginLockouts } from "@/components/LoginLockouts";
+import { ReceiptVerifier } from "@/components/ReceiptVerifier";
+import { WithdrawnFiles } from "@/components/WithdrawnFiles";
+import { UploadRulesForm } from "@/components/UploadRulesForm";
+import { TimeExtensionControl } from "@/components/TimeExtensionControl";
 import { useTimerAlert } from "@/components/TimerAlert";
 import { Button } from "@/components/ui/button";
 import { Input } from "@/components/ui/input";
@@ -27,19 +35,27 @@ import { Separator } from "@/components/ui/separator";
 import { Progress } from "@/components/ui/progress";
 
 export default function AdminPage() {
-  const { state, connected } = useWebSocket();
   const [isAuthenticated, setIsAuthenticated] = useState<boolean | null>(null);
+  const { state, connected } = useWebSocket("adminToken", isAuthenticated);
   const [jumlahMeja, setJumlahMeja] = useState(10);
   const [timerMinutes, setTimerMinutes] = useState(60);
+  const [graceMinutes, setGraceMinutes] = useState(0);
   const soalInputRef = useRef<HTMLInputElement>(null);
   const [hasSoalFiles, setHasSoalFiles] = useState(false);
 
   const [archiveView, setArchiveView] = useState<{
-    path: string;
+    mejaId: string;
+    fileId: string;
     filename: string;
   } | null>(null);
   const [fileView, setFileView] = useState<{
-    path: string;
+    mejaId: string;
+    fileId: string;
+    filename: string;
+  } | null>(null);
+  const [diffView, setDiffView] = useState<{
+    mejaId: string;
+    fileId: string;
     filename: string;
   } | null>(null);
   const [uploadProgress, setUploadProgress] = useState<number | null>(null);
@@ -51,6 +67,11 @@ export default function AdminPage() {
       duration_seconds: 0,
       remaining_seconds: 0,
       started_at: null,
+      deadline: null,
+      paused_at: null,
+      paused_seconds: 0,
+      ended_at: null,
+      grace_seconds: 0,
     },
     true
   );
@@ -74,6 +95,7 @@ export default function AdminPage() {
   }, []);
 
   const handleLogout = () => {
+    api.logout().catch(() => {});
     localStorage.removeItem("adminToken");
     setIsAuthenticated(false);
   };
@@ -130,7 +152,7 @@ export default function AdminPage() {
             ${row.map(meja => `
               <div class="card">
                 <div class="meja-name">Meja ${meja.nomor}</div>
-                <div class="meja-kode">${meja.kode.toUpperCase()}</div>
+                <div class="meja-kode">${(meja.kode ?? '-').toUpperCase()}</div>
               </div>
             `).join('')}
             ${row.length < 3 ? Array(3 - row.length).fill('<div class="card empty"></div>').join('') : ''}
@@ -178,6 +200,7 @@ export default function AdminPage() {
     (a, b) => a.nomor - b.nomor
   );
   const mejaWithFiles = mejaList.filter((m) => m.files.length > 0);
+  const mejaSubmitted = mejaList.filter((m) => m.submitted_at);
   const totalFiles = mejaList.reduce((sum, m) => sum + m.files.length, 0);
 
   const handleGenerateMeja = async () => {
@@ -216,14 +239,15 @@ export default function AdminPage() {
 
   const isArchive = (filename: string) => {
     const ext = filename.toLowerCase();
-    return ext.endsWith(".zip") || ext.endsWith(".rar");
+    return [".zip", ".rar", ".7z", ".tar", ".tar.gz", ".tgz", ".tar.xz", ".txz"].some((e) => ext.endsWith(e));
   };
 
-  const openFilePreview = (file: FileInfo) => {
+  const openFilePreview = (mejaId: string, file: FileInfo) => {
+    const view = { mejaId, fileId: file.id, filename: file.filename };
     if (isArchive(file.filename)) {
-      setArchiveView({ path: file.path, filename: file.filename });
+      setArchiveView(view);
     } else {
-      setFileView({ path: file.path, filename: file.filename });
+      setFileView(view);
     }
   };
 
@@ -349,6 +373,26 @@ export default function AdminPage() {
                   </div>
                   <Button onClick={handleSetTimer}>Set Timer</Button>
                 </div>
+                <div className="flex items-end gap-4">
+                  <div className="flex-1">
+                    <Label>Masa Tenggang (menit)</Label>
+                    <Input
+                      type="number"
+                      value={graceMinutes}
+                      onChange={(e) =>
+                        setGraceMinutes(parseInt(e.target.value) || 0)
+                      }
+                      min={0}
+                    />
+                    <p className="text-xs text-muted-foreground mt-1">
+                      Saat ini {Math.round(state.timer.grace_seconds / 60)} menit. Upload setelah
+                      waktu habis tetap diterima selama masa tenggang, ditandai terlambat.
+                    </p>
+                  </div>
+                  <Button variant="outline" onClick={() => api.setGracePeriod(graceMinutes)}>
+                    Set Tenggang
+                  </Button>
+                </div>
               </CardContent>
             </Card>
 
@@ -370,6 +414,13 @@ export default function AdminPage() {
                   <div className="text-muted-foreground">Sudah Upload</div>
                 </div>
                 <Separator />
+                <div className="text-center">
+                  <div className="text-4xl font-bold text-purple-600">
+                    {mejaSubmitted.length}
+                  </div>
+                  <div className="text-muted-foreground">Final Submit</div>
+                </div>
+                <Separator />
                 <div className="text-center">
                   <div className="text-4xl font-bold text-blue-600">
                     {totalFiles}
@@ -414,6 +465,8 @@ export default function AdminPage() {
             </CardContent>
           </Card>
 
+          {state.upload_rules && <UploadRulesForm rules={state.upload_rules} />}
+
           {/* Recent Uploads */}
           {mejaWithFiles.length > 0 && (
             <Card>
@@ -446,9 +499,14 @@ export default function AdminPage() {
                             </div>
                           </div>
                           <div className="text-right">
-                            <Badge variant="secondary">
-                              {meja.files.length} file
-                            </Badge>
+                            <div className="flex items-center justify-end gap-1">
+                              {meja.submitted_at && (
+                                <Badge variant="default">Selesai</Badge>
+                              )}
+                              <Badge variant="secondary">
+                                {meja.files.length} file
+                              </Badge>
+                            </div>
                             {meja.last_upload && (
                               <div className="text-xs text-muted-foreground mt-1">
                                 {new Date(
@@ -464,16 +522,25 @@ export default function AdminPage() {
               </CardContent>
             </Card>
           )}
+
+          <LoginLockouts />
         </TabsContent>
 
-        <TabsContent value="meja">
+        <TabsContent value="meja" className="space-y-6">
           <Card>
             <CardHeader className="flex flex-row items-center justify-between">
               <CardTitle>Daftar Meja ({mejaList.length})</CardTitle>
               {mejaList.length > 0 && (
-                <Button variant="outline" size="sm" onClick={handleExportPdf}>
-                  Export PDF
-                </Button>
+                <div className="flex gap-2">
+                  <a href={api.getSubmissionsZipUrl()}>
+                    <Button variant="outline" size="sm">
+                      Download Semua (ZIP)
+                    </Button>
+                  </a>
+                  <Button variant="outline" size="sm" onClick={handleExportPdf}>
+                    Export PDF
+                  </Button>
+                </div>
               )}
             </CardHeader>
             <CardContent>
@@ -491,6 +558,7 @@ export default function AdminPage() {
                         <TableHead>Peserta</TableHead>
                         <TableHead>Files</TableHead>
                         <TableHead>Status</TableHead>
+                        <TableHead>Tambahan Waktu</TableHead>
                       </TableRow>
                     </TableHeader>
                     <TableBody>
@@ -501,42 +569,72 @@ export default function AdminPage() {
                           </TableCell>
                           <TableCell>
                             <code className="bg-muted px-2 py-1 rounded">
-                              {meja.kode}
+                              {meja.kode ?? "-"}
                             </code>
                           </TableCell>
                           <TableCell>{meja.nama_peserta || "-"}</TableCell>
                           <TableCell>
                             {meja.files.length > 0 ? (
                               <div className="space-y-2">
-                                {[...meja.files]
-                                  .sort(
-                                    (a, b) =>
-                                      new Date(b.uploaded_at).getTime() -
-                                      new Date(a.uploaded_at).getTime()
-                                  )
-                                  .map((file) => (
+                                {sortByVersion(meja.files).map((file) => (
                                     <div
                                       key={file.id}
                                       className="flex flex-col gap-1 p-2 bg-muted/30 rounded text-sm"
                                     >
                                       <div className="flex items-center justify-between gap-2">
-                                        <span className="font-medium truncate max-w-[200px]">
-                                          {file.filename}
-                                        </span>
+                                        <div className="flex items-center gap-1 min-w-0">
+                                          <span className="font-medium truncate max-w-[200px]">
+                                            {file.filename}
+                                          </span>
+                                          <Badge variant="outline" className="text-xs">
+                                            v{file.version}
+                                          </Badge>
+                                          {isSubmittedVersion(meja.files, file) && (
+                                            <Badge
+                                              variant={file.is_final ? "default" : "secondary"}
+                                              className="text-xs"
+                                            >
+                                              {file.is_final ? "Final" : "Terbaru"}
+                                            </Badge>
+                                          )}
+                                          {file.late && (
+                                            <Badge variant="destructive" className="text-xs">
+                                              Terlambat {formatSecondsLate(file.seconds_late ?? 0)}
+                                            </Badge>
+                                          )}
+                                        </div>
                                         <div className="flex items-center gap-1">
+                                          {file.version > 1 && (
+                                            <Button
+                                              variant="ghost"
+                                              size="sm"
+                                              className="h-6 px-2 text-xs"
+                                              onClick={() =>
+                                                setDiffView({
+                                                  mejaId: meja.id,
+                                                  fileId: file.id,
+                                                  filename: file.filename,
+                                                })
+                                              }
+                                            >
+                                              Diff
+                                            </Button>
+                                          )}
                                           <Button
                                             variant="ghost"
                                             size="sm"
                                             className="h-6 px-2 text-xs"
                                             onClick={() =>
-                                              openFilePreview(file)
+                                              openFilePreview(meja.id, file)
                                             }
                                           >
                                             Preview
                                           </Button>
                                           <a
                                             href={api.getFileDownloadUrl(
-                                              file.path
+                                              meja.id,
+                                              file.id,
+                                              "adminToken"
                                             )}
                                             download={file.filename}
                                           >
@@ -567,12 +665,30 @@ export default function AdminPage() {
                             )}
                           </TableCell>
                           <TableCell>
-                            {meja.files.length > 0 ? (
-                              <Badge variant="default">Sudah Upload</Badge>
+                            {meja.submitted_at ? (
+                              <div className="flex flex-col items-start gap-1">
+                                <Badge variant="default">Selesai</Badge>
+                                <span className="text-xs text-muted-foreground">
+                                  {new Date(meja.submitted_at).toLocaleString("id-ID")}
+                                </span>
+                                <Button
+                                  variant="outline"
+                                  size="sm"
+                                  className="h-6 px-2 text-xs"
+                                  onClick={() => api.reopenMeja(meja.id)}
+                                >
+                                  Buka Lagi
+                                </Button>
+                              </div>
+                            ) : meja.files.length > 0 ? (
+                              <Badge variant="secondary">Sudah Upload</Badge>
                             ) : (
                               <Badge variant="outline">Belum Upload</Badge>
                             )}
                           </TableCell>
+                          <TableCell>
+                            <TimeExtensionControl meja={meja} />
+                          </TableCell>
                         </TableRow>
                       ))}
                     </TableBody>
@@ -581,6 +697,10 @@ export default function AdminPage() {
               )}
             </CardContent>
           </Card>
+
+          <WithdrawnFiles mejaList={mejaList} />
+
+          <ReceiptVerifier />
         </TabsContent>
 
         <TabsContent value="soal">
@@ -653,7 +773,9 @@ export default function AdminPage() {
 
       {archiveView && (
         <ArchiveViewer
-          path={archiveView.path}
+          mejaId={archiveView.mejaId}
+          fileId={archiveView.fileId}
+          tokenKey="adminToken"
           filename={archiveView.filename}
           open={!!archiveView}
           onClose={() => setArchiveView(null)}
@@ -662,12 +784,24 @@ export default function AdminPage() {
 
       {fileView && (
         <FileViewer
-          path={fileView.path}
+          mejaId={fileView.mejaId}
+          fileId={fileView.fileId}
+          tokenKey="adminToken"
           filename={fileView.filename}
           open={!!fileView}
           onClose={() => setFileView(null)}
         />
       )}
+
+      {diffView && (
+        <DiffViewer
+          mejaId={diffView.mejaId}
+          fileId={diffView.fileId}
+          filename={diffView.filename}
+          open={!!diffView}
+          onClose={() => setDiffView(null)}
+        />
+      )}
     </div>
   );
 }
@@ -7,6 +7,8 @@ import type { Meja, SoalFile, FileInfo } from "@/lib/types";
 import { Timer } from "@/components/Timer";
 import { ArchiveViewer, FileViewer } from "@/components/ArchiveViewer";
 import { useTimerAlert } from "@/components/TimerAlert";
+import { isSubmittedVersion, sortByVersion } from "@/lib/versions";
+import { formatSecondsLate, graceRemaining, isUploadClosed } from "@/lib/lateness";
 import { Button } from "@/components/ui/button";
 import { Input } from "@/components/ui/input";
 import { Label } from "@/components/ui/label";
@@ -23,14 +25,21 @@ import { Separator } from "@/components/ui/separator";
 import { Progress } from "@/components/ui/progress";
 
 export default function PesertaPage() {
-  const { state, connected } = useWebSocket();
   const [kode, setKode] = useState("");
   const [meja, setMeja] = useState<Meja | null>(null);
+  const { state, connected } = useWebSocket("pesertaToken", meja?.id);
   const [nama, setNama] = useState("");
   const [error, setError] = useState("");
   const [uploadProgress, setUploadProgress] = useState<number | null>(null);
   const [hasFiles, setHasFiles] = useState(false);
   const fileInputRef = useRef<HTMLInputElement>(null);
+  // Dipakai untuk menghitung mundur masa tenggang; server tidak broadcast setelah timer habis
+  const [now, setNow] = useState(Date.now());
+
+  useEffect(() => {
+    const interval = setInterval(() => setNow(Date.now()), 1000);
+    return () => clearInterval(interval);
+  }, []);
 
   // Timer alert hook (always enabled when logged in)
   useTimerAlert(
@@ -39,16 +48,23 @@ export default function PesertaPage() {
       duration_seconds: 0,
       remaining_seconds: 0,
       started_at: null,
+      deadline: null,
+      paused_at: null,
+      paused_seconds: 0,
+      ended_at: null,
+      grace_seconds: 0,
     },
     meja !== null
   );
 
   const [archiveView, setArchiveView] = useState<{
-    path: string;
+    mejaId: string;
+    fileId: string;
     filename: string;
   } | null>(null);
   const [fileView, setFileView] = useState<{
-    path: string;
+    mejaId: string;
+    fileId: string;
     filename: string;
   } | null>(null);
 
@@ -60,6 +76,7 @@ export default function PesertaPage() {
       setKode(savedKode);
       api.login(savedKode).then((res) => {
         if (res.success) {
+          localStorage.setItem("pesertaToken", res.token);
           setMeja(res.meja);
           setNama(res.meja.nama_peserta || "");
         }
@@ -85,6 +102,7 @@ export default function PesertaPage() {
       setNama(res.meja.nama_peserta || "");
       localStorage.setItem("mejaId", res.meja.id);
       localStorage.setItem("mejaKode", kode);
+      localStorage.setItem("pesertaToken", res.token);
     } else {
       setError(res.error || "Kode tidak valid");
     }
@@ -96,6 +114,71 @@ export default function PesertaPage() {
     setNama("");
     localStorage.removeItem("mejaId");
     localStorage.removeItem("mejaKode");
+    localStorage.removeItem("pesertaToken");
+  };
+
+  const handleMarkFinal = async (file: FileInfo) => {
+    if (!meja) return;
+    setError("");
+    const res = await api.markFileFinal(meja.id, file.id);
+    if (!res.success) {
+      setError(res.error || "Gagal menandai versi final");
+    }
+  };
+
+  const handleDeleteFile = async (file: FileInfo) => {
+    if (!meja) return;
+    if (!window.confirm(`Hapus ${file.filename} (v${file.version})?`)) return;
+    setError("");
+    const res = await api.deleteMejaFile(meja.id, file.id);
+    if (!res.success) {
+      setError(res.error || "Gagal menghapus file");
+    }
+  };
+
+  const handleReplaceFile = (file: FileInfo) => {
+    if (!meja) return;
+    const input = document.createElement("input");
+    input.type = "file";
+    input.onchange = async () => {
+      const replacement = input.files?.[0];
+      if (!replacement) return;
+      setError("");
+      const res = await api.replaceMejaFile(meja.id, file.id, replacement);
+      if (!res.success) {
+        setError(res.error || "Gagal mengganti file");
+      }
+    };
+    input.click();
+  };
+
+  // Bukti upload disimpan peserta sebagai file JSON untuk ditunjukkan jika ada sengketa
+  const handleDownloadReceipt = async (file: FileInfo) => {
+    if (!meja) return;
+    const receipt = await api.getFileReceipt(meja.id, file.id);
+    if (receipt.error) {
+      setError(receipt.error);
+      return;
+    }
+    const blob = new Blob([JSON.stringify(receipt, null, 2)], { type: "application/json" });
+    const url = URL.createObjectURL(blob);
+    const link = document.createElement("a");
+    link.href = url;
+    link.download = `bukti_${file.filename}_v${file.version}.json`;
+    link.click();
+    URL.revokeObjectURL(url);
+  };
+
+  const handleSubmit = async () => {
+    if (!meja) return;
+    if (!window.confirm("Setelah final submit, file tidak bisa diubah lagi kecuali dibuka oleh panitia. Lanjutkan?")) {
+      return;
+    }
+    setError("");
+    const res = await api.submitMeja(meja.id);
+    if (!res.success) {
+      setError(res.error || "Final submit gagal");
+    }
   };
 
   const handleUpdateNama = async () => {
@@ -106,6 +189,24 @@ export default function PesertaPage() {
 
   const MAX_FILE_SIZE = 300 * 1024 * 1024; // 300MB
 
+  // Batas dari admin beserta pemakaian meja saat ini
+  const uploadRulesHint = () => {
+    const rules = state?.upload_rules;
+    if (!rules || !meja) return null;
+    const parts = [];
+    if (rules.max_files) {
+      parts.push(`${meja.files.length}/${rules.max_files} file`);
+    }
+    if (rules.max_total_bytes) {
+      const used = meja.files.reduce((sum, f) => sum + f.size, 0);
+      parts.push(`${formatSize(used)} dari kuota ${formatSize(rules.max_total_bytes)}`);
+    }
+    if (rules.allowed_types.length > 0) {
+      parts.push(`tipe: ${rules.allowed_types.join(", ")}`);
+    }
+    return parts.length > 0 ? parts.join(" - ") : null;
+  };
+
   const handleUpload = async () => {
     const files = fileInputRef.current?.files;
     if (!files || files.length === 0 || !meja) return;
@@ -121,9 +222,15 @@ export default function PesertaPage() {
     setUploadProgress(0);
     setError("");
     try {
-      await api.uploadFileWithProgress(meja.id, files, (progress) => {
-        setUploadProgress(progress);
-      });
+      const totalSize = Array.from(files).reduce((sum, f) => sum + f.size, 0);
+      let doneSize = 0;
+      for (const file of Array.from(files)) {
+        await api.uploadFileResumable(meja.id, file, (progress) => {
+          const loaded = doneSize + (file.size * progress) / 100;
+          setUploadProgress(totalSize ? Math.round((loaded / totalSize) * 100) : progress);
+        });
+        doneSize += file.size;
+      }
     } catch (e: unknown) {
       const errorMessage = e instanceof Error ? e.message : "Upload gagal";
       if (
@@ -141,11 +248,11 @@ export default function PesertaPage() {
     }
   };
 
-  // Check if time is expired
-  const isTimeExpired =
-    state?.timer &&
-    state.timer.remaining_seconds <= 0 &&
-    state.timer.duration_seconds > 0;
+  // Timer habis dan masa tenggang (jika ada) sudah lewat
+  const isTimeExpired = !!state?.timer && isUploadClosed(state.timer, now);
+  const lateUploadSeconds = state?.timer ? graceRemaining(state.timer, now) : 0;
+  const isSubmitted = !!meja?.submitted_at;
+  const isLocked = isTimeExpired || isSubmitted;
 
   const formatSize = (bytes: number) => {
     if (bytes < 1024) return `${bytes} B`;
@@ -155,14 +262,15 @@ export default function PesertaPage() {
 
   const isArchive = (filename: string) => {
     const ext = filename.toLowerCase();
-    return ext.endsWith(".zip") || ext.endsWith(".rar");
+    return [".zip", ".rar", ".7z", ".tar", ".tar.gz", ".tgz", ".tar.xz", ".txz"].some((e) => ext.endsWith(e));
   };
 
-  const openFilePreview = (file: FileInfo) => {
+  const openFilePreview = (mejaId: string, file: FileInfo) => {
+    const view = { mejaId, fileId: file.id, filename: file.filename };
     if (isArchive(file.filename)) {
-      setArchiveView({ path: file.path, filename: file.filename });
+      setArchiveView(view);
     } else {
-      setFileView({ path: file.path, filename: file.filename });
+      setFileView(view);
     }
   };
 
@@ -307,6 +415,37 @@ export default function PesertaPage() {
               </Alert>
             )}
 
+            {lateUploadSeconds > 0 && !isSubmitted && (
+              <Alert variant="destructive">
+                <AlertDescription>
+                  Waktu habis. Upload masih diterima sebagai TERLAMBAT selama{" "}
+                  {formatSecondsLate(lateUploadSeconds)} lagi.
+                </AlertDescription>
+              </Alert>
+            )}
+
+            {meja && meja.extensions.length > 0 && !isTimeExpired && (
+              <Alert>
+                <AlertDescription>
+                  Meja ini mendapat tambahan waktu{" "}
+                  {meja.extensions
+                    .map((ext) => `${formatSecondsLate(ext.seconds)} (${ext.reason})`)
+                    .join(", ")}
+                  . Timer di atas sudah termasuk tambahan waktu.
+                </AlertDescription>
+              </Alert>
+            )}
+
+            {isSubmitted && !isTimeExpired && (
+              <Alert>
+                <AlertDescription>
+                  Sudah final submit pada{" "}
+                  {new Date(meja.submitted_at!).toLocaleString()}. Hubungi
+                  panitia jika perlu mengubah file.
+                </AlertDescription>
+              </Alert>
+            )}
+
             {error && !isTimeExpired && (
               <Alert variant="destructive">
                 <AlertDescription>{error}</AlertDescription>
@@ -320,14 +459,17 @@ export default function PesertaPage() {
                   type="file"
                   ref={fileInputRef}
                   multiple
-                  accept=".zip,.rar,*"
-                  disabled={uploadProgress !== null || isTimeExpired}
+                  accept={state?.upload_rules?.allowed_types.join(",") || ".zip,.rar,*"}
+                  disabled={uploadProgress !== null || isLocked}
                   onChange={(e) => setHasFiles((e.target.files?.length ?? 0) > 0)}
                 />
+                {uploadRulesHint() && (
+                  <p className="text-sm text-muted-foreground mt-1">{uploadRulesHint()}</p>
+                )}
               </div>
               <Button
                 onClick={handleUpload}
-                disabled={uploadProgress !== null || isTimeExpired || !hasFiles}
+                disabled={uploadProgress !== null || isLocked || !hasFiles}
                 className="w-full"
               >
                 {uploadProgress !== null ? "Uploading..." : "Upload"}
@@ -352,31 +494,98 @@ export default function PesertaPage() {
                     File yang sudah diupload:
                   </Label>
                   <div className="space-y-2 mt-2">
-                    {meja.files.map((file) => (
-                      <div
-                        key={file.id}
-                        className="flex items-center justify-between p-3 bg-muted/50 rounded-lg"
-                      >
-                        <div>
-                          <div className="font-medium">{file.filename}</div>
-                          <div className="text-sm text-muted-foreground">
-                            {formatSize(file.size)} -{" "}
-                            {new Date(file.uploaded_at).toLocaleString()}
+                    {sortByVersion(meja.files).map((file) => {
+                      const submitted = isSubmittedVersion(meja.files, file);
+                      return (
+                        <div
+                          key={file.id}
+                          className="flex items-center justify-between p-3 bg-muted/50 rounded-lg"
+                        >
+                          <div>
+                            <div className="flex items-center gap-2 font-medium">
+                              {file.filename}
+                              <Badge variant="outline">v{file.version}</Badge>
+                              {submitted && (
+                                <Badge variant={file.is_final ? "default" : "secondary"}>
+                                  {file.is_final ? "Final" : "Terbaru"}
+                                </Badge>
+                              )}
+                              {file.late && (
+                                <Badge variant="destructive">
+                                  Terlambat {formatSecondsLate(file.seconds_late ?? 0)}
+                                </Badge>
+                              )}
+                            </div>
+                            <div className="text-sm text-muted-foreground">
+                              {formatSize(file.size)} -{" "}
+                              {new Date(file.uploaded_at).toLocaleString()}
+                            </div>
+                          </div>
+                          <div className="flex items-center gap-2">
+                            {file.sha256 && (
+                              <Button
+                                variant="ghost"
+                                size="sm"
+                                onClick={() => handleDownloadReceipt(file)}
+                              >
+                                Bukti
+                              </Button>
+                            )}
+                            {!file.is_final && (
+                              <Button
+                                variant="ghost"
+                                size="sm"
+                                disabled={isLocked}
+                                onClick={() => handleMarkFinal(file)}
+                              >
+                                Jadikan Final
+                              </Button>
+                            )}
+                            {!isLocked && (
+                              <>
+                                <Button
+                                  variant="ghost"
+                                  size="sm"
+                                  disabled={uploadProgress !== null}
+                                  onClick={() => handleReplaceFile(file)}
+                                >
+                                  Ganti
+                                </Button>
+                                <Button
+                                  variant="ghost"
+                                  size="sm"
+                                  className="text-destructive"
+                                  onClick={() => handleDeleteFile(file)}
+                                >
+                                  Hapus
+                                </Button>
+                              </>
+                            )}
+                            {isArchive(file.filename) && (
+                              <Button
+                                variant="outline"
+                                size="sm"
+                                onClick={() => openFilePreview(meja.id, file)}
+                              >
+                                Lihat Isi
+                              </Button>
+                            )}
                           </div>
                         </div>
-                        {isArchive(file.filename) && (
-                          <Button
-                            variant="outline"
-                            size="sm"
-                            onClick={() => openFilePreview(file)}
-                          >
-                            Lihat Isi
-                          </Button>
-                        )}
-                      </div>
-                    ))}
+                      );
+                    })}
                   </div>
                 </div>
+                {!isLocked && (
+                  <Button
+                    variant="secondary"
+                    className="w-full"
+                    disabled={uploadProgress !== null}
+                    onClick={handleSubmit}
+                  >
+                    Final Submit
+                  </Button>
+                )}
               </>
             )}
           </CardContent>
@@ -385,7 +594,9 @@ export default function PesertaPage() {
 
       {archiveView && (
         <ArchiveViewer
-          path={archiveView.path}
+          mejaId={archiveView.mejaId}
+          fileId={archiveView.fileId}
+          tokenKey="pesertaToken"
           filename={archiveView.filename}
           open={!!archiveView}
           onClose={() => setArchiveView(null)}
@@ -394,7 +605,9 @@ export default function PesertaPage() {
 
       {fileView && (
         <FileViewer
-          path={fileView.path}
+          mejaId={fileView.mejaId}
+          fileId={fileView.fileId}
+          tokenKey="pesertaToken"
           filename={fileView.filename}
           open={!!fileView}
           onClose={() => setFileView(null)}
@@ -1,7 +1,7 @@
 'use client';
 
 import { useState, useEffect, useMemo } from 'react';
-import { api } from '@/lib/api';
+import { api, type TokenKey } from '@/lib/api';
 import type { ArchiveContent, FilePreview, ArchiveEntry } from '@/lib/types';
 import { ScrollArea } from '@/components/ui/scroll-area';
 import {
@@ -30,7 +30,9 @@ interface TreeFileNode {
   children: TreeFileNode[];
 }
 
-function buildFileTree(files: ArchiveEntry[]): TreeFileNode[] {
+// Archive di dalam archive (`children`) ditampilkan sebagai folder; path
+// entry di dalamnya memakai pemisah `!/`, mis. `build.zip!/src/a.c`
+function buildFileTree(files: ArchiveEntry[], prefix = ''): TreeFileNode[] {
   const root: TreeFileNode[] = [];
   
   for (const file of files) {
@@ -42,17 +44,18 @@ function buildFileTree(files: ArchiveEntry[]): TreeFileNode[] {
       const part = parts[i];
       currentPath = currentPath ? `${currentPath}/${part}` : part;
       const isLastPart = i === parts.length - 1;
-      const isDir = isLastPart ? file.is_dir : true;
+      const isNestedArchive = isLastPart && file.children !== null;
+      const isDir = isLastPart ? file.is_dir || isNestedArchive : true;
       
       let existing = currentLevel.find(n => n.name === part);
       
       if (!existing) {
         existing = {
           name: part,
-          path: currentPath,
+          path: `${prefix}${currentPath}`,
           isDir: isDir,
           size: isLastPart ? file.size : 0,
-          children: [],
+          children: isNestedArchive ? buildFileTree(file.children ?? [], `${prefix}${currentPath}!/`) : [],
         };
         currentLevel.push(existing);
       }
@@ -86,12 +89,12 @@ function formatSize(bytes: number) {
   return `${(bytes / (1024 * 1024)).toFixed(1)} MB`;
 }
 
-function FileTreeNode({ node, level, isLast }: { node: TreeFileNode; level: number; isLast: boolean }) {
+function FileTreeNode({ node, level, isLast, onSelect }: { node: TreeFileNode; level: number; isLast: boolean; onSelect: (path: string) => void }) {
   const hasChildren = node.children.length > 0;
   
   return (
     <TreeNode nodeId={node.path} level={level} isLast={isLast}>
-      <TreeNodeTrigger>
+      <TreeNodeTrigger onClick={() => !node.isDir && onSelect(node.path)}>
         <TreeExpander hasChildren={hasChildren} />
         <TreeIcon hasChildren={node.isDir} />
         <TreeLabel>{node.name}</TreeLabel>
@@ -109,6 +112,7 @@ function FileTreeNode({ node, level, isLast }: { node: TreeFileNode; level: numb
               node={child}
               level={level + 1}
               isLast={idx === node.children.length - 1}
+              onSelect={onSelect}
             />
           ))}
         </TreeNodeContent>
@@ -118,27 +122,46 @@ function FileTreeNode({ node, level, isLast }: { node: TreeFileNode; level: numb
 }
 
 interface ArchiveViewerProps {
-  path: string;
+  mejaId: string;
+  fileId: string;
+  tokenKey: TokenKey;
   filename: string;
   open: boolean;
   onClose: () => void;
 }
 
-export function ArchiveViewer({ path, filename, open, onClose }: ArchiveViewerProps) {
+export function ArchiveViewer({ mejaId, fileId, tokenKey, filename, open, onClose }: ArchiveViewerProps) {
   const [content, setContent] = useState<ArchiveContent | null>(null);
   const [loading, setLoading] = useState(false);
   const [error, setError] = useState<string | null>(null);
+  const [selectedEntry, setSelectedEntry] = useState<string | null>(null);
+  const [entryPreview, setEntryPreview] = useState<FilePreview | null>(null);
+  const [entryLoading, setEntryLoading] = useState(false);
+  const [entryError, setEntryError] = useState<string | null>(null);
 
   useEffect(() => {
-    if (open && path) {
+    if (open && fileId) {
       setLoading(true);
       setError(null);
-      api.previewArchive(path)
-        .then(setContent)
+      setContent(null);
+      setSelectedEntry(null);
+      api.previewArchive(mejaId, fileId, tokenKey)
+        .then((data) => (data.error ? setError(data.error) : setContent(data)))
         .catch(() => setError('Failed to load archive'))
         .finally(() => setLoading(false));
     }
-  }, [open, path]);
+  }, [open, mejaId, fileId, tokenKey]);
+
+  const openEntry = (name: string) => {
+    setSelectedEntry(name);
+    setEntryPreview(null);
+    setEntryError(null);
+    setEntryLoading(true);
+    api.previewArchiveEntry(mejaId, fileId, name, tokenKey)
+      .then((data) => (data?.error ? setEntryError(data.error) : setEntryPreview(data)))
+      .catch(() => setEntryError('Failed to load file'))
+      .finally(() => setEntryLoading(false));
+  };
 
   const fileTree = useMemo(() => {
     if (!content) return [];
@@ -161,15 +184,66 @@ export function ArchiveViewer({ path, filename, open, onClose }: ArchiveViewerPr
                 {content.files.length} items
               </Badge>
             )}
+            {content?.encrypted && (
+              <Badge variant="destructive">Terenkripsi</Badge>
+            )}
           </DialogTitle>
         </DialogHeader>
         <ScrollArea className="h-[60vh]">
           {loading && <p className="text-center py-4">Loading...</p>}
           {error && <p className="text-center py-4 text-red-500">{error}</p>}
-          {content && content.files.length === 0 && (
-            <p className="text-center py-4 text-muted-foreground">Archive kosong atau format tidak didukung</p>
+          {!selectedEntry && content && content.warnings.length > 0 && (
+            <ul className="mb-3 space-y-1 rounded border border-yellow-500/50 bg-yellow-500/10 p-3 text-sm">
+              {content.warnings.map((w, idx) => (
+                <li key={idx}>
+                  {w.message}
+                  {w.entry && <span className="ml-1 font-mono text-xs">({w.entry})</span>}
+                </li>
+              ))}
+            </ul>
           )}
-          {content && content.files.length > 0 && (
+          {!selectedEntry && content && content.files.length === 0 && (
+            <p className="text-center py-4 text-muted-foreground">
+              {content.encrypted
+                ? 'Archive terenkripsi dan tidak bisa dibuka tanpa password'
+                : 'Archive kosong'}
+            </p>
+          )}
+          {selectedEntry && (
+            <div className="space-y-3">
+              <div className="flex items-center justify-between gap-2">
+                <button
+                  type="button"
+                  className="text-sm text-muted-foreground hover:underline"
+                  onClick={() => setSelectedEntry(null)}
+                >
+                  &larr; Kembali
+                </button>
+                <span className="truncate font-mono text-sm">{selectedEntry}</span>
+                <a
+                  href={api.getArchiveEntryDownloadUrl(mejaId, fileId, selectedEntry, tokenKey)}
+                  className="text-sm text-primary hover:underline"
+                >
+                  Download
+                </a>
+              </div>
+              {entryLoading && <p className="text-center py-4">Loading...</p>}
+              {entryError && <p className="text-center py-4 text-red-500">{entryError}</p>}
+              {!entryLoading && !entryError && !entryPreview?.content && (
+                <p className="text-center py-4 text-muted-foreground">
+                  {entryPreview?.is_text
+                    ? 'File terlalu besar untuk di-preview, silakan download'
+                    : 'File ini bukan file teks dan tidak bisa di-preview'}
+                </p>
+              )}
+              {entryPreview?.content && (
+                <pre className="text-sm bg-muted p-4 rounded overflow-x-auto whitespace-pre-wrap">
+                  {entryPreview.content}
+                </pre>
+              )}
+            </div>
+          )}
+          {!selectedEntry && content && content.files.length > 0 && (
             <TreeProvider
               defaultExpandedIds={defaultExpanded}
               showLines={true}
@@ -183,6 +257,7 @@ export function ArchiveViewer({ path, filename, open, onClose }: ArchiveViewerPr
                     node={node}
                     level={0}
                     isLast={idx === fileTree.length - 1}
+                    onSelect={openEntry}
                   />
                 ))}
               </TreeView>
@@ -195,24 +270,26 @@ export function ArchiveViewer({ path, filename, open, onClose }: ArchiveViewerPr
 }
 
 interface FileViewerProps {
-  path: string;
+  mejaId: string;
+  fileId: string;
+  tokenKey: TokenKey;
   filename: string;
   open: boolean;
   onClose: () => void;
 }
 
-export function FileViewer({ path, filename, open, onClose }: FileViewerProps) {
+export function FileViewer({ mejaId, fileId, tokenKey, filename, open, onClose }: FileViewerProps) {
   const [preview, setPreview] = useState<FilePreview | null>(null);
   const [loading, setLoading] = useState(false);
 
   useEffect(() => {
-    if (open && path) {
+    if (open && fileId) {
       setLoading(true);
-      api.previewFile(path)
+      api.previewFile(mejaId, fileId, tokenKey)
         .then(setPreview)
         .finally(() => setLoading(false));
     }
-  }, [open, path]);
+  }, [open, mejaId, fileId, tokenKey]);
 
   return (
     <Dialog open={open} onOpenChange={(o) => !o && onClose()}>
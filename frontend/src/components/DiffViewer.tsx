@@ -0,0 +1,74 @@
+'use client';
+
+import { useState, useEffect } from 'react';
+import { api } from '@/lib/api';
+import type { FileDiff } from '@/lib/types';
+import { ScrollArea } from '@/components/ui/scroll-area';
+import {
+  Dialog,
+  DialogContent,
+  DialogHeader,
+  DialogTitle,
+} from '@/components/ui/dialog';
+
+interface DiffViewerProps {
+  mejaId: string;
+  fileId: string;
+  filename: string;
+  open: boolean;
+  onClose: () => void;
+}
+
+function lineClass(line: string) {
+  if (line.startsWith('+') && !line.startsWith('+++')) return 'bg-green-500/15 text-green-700 dark:text-green-400';
+  if (line.startsWith('-') && !line.startsWith('---')) return 'bg-red-500/15 text-red-700 dark:text-red-400';
+  if (line.startsWith('@@')) return 'text-blue-600 dark:text-blue-400';
+  return '';
+}
+
+// Perbandingan satu versi dengan versi sebelumnya (khusus admin)
+export function DiffViewer({ mejaId, fileId, filename, open, onClose }: DiffViewerProps) {
+  const [diff, setDiff] = useState<FileDiff | null>(null);
+  const [error, setError] = useState('');
+  const [loading, setLoading] = useState(false);
+
+  useEffect(() => {
+    if (open && fileId) {
+      setLoading(true);
+      setDiff(null);
+      setError('');
+      api.diffMejaFile(mejaId, fileId)
+        .then((result) => (result.error ? setError(result.error) : setDiff(result)))
+        .finally(() => setLoading(false));
+    }
+  }, [open, mejaId, fileId]);
+
+  return (
+    <Dialog open={open} onOpenChange={(o) => !o && onClose()}>
+      <DialogContent className="max-w-4xl max-h-[80vh]">
+        <DialogHeader>
+          <DialogTitle>
+            Diff: {filename}
+            {diff && ` (v${diff.from.version} → v${diff.to.version})`}
+          </DialogTitle>
+        </DialogHeader>
+        <ScrollArea className="h-[60vh]">
+          {loading && <p className="text-center py-4">Loading...</p>}
+          {error && <p className="text-center py-4 text-destructive">{error}</p>}
+          {diff && diff.identical && (
+            <p className="text-center py-4 text-muted-foreground">Isi kedua versi sama</p>
+          )}
+          {diff && !diff.identical && (
+            <pre className="text-sm bg-muted p-4 rounded overflow-x-auto">
+              {diff.diff.split('\n').map((line, i) => (
+                <div key={i} className={lineClass(line)}>
+                  {line || ' '}
+                </div>
+              ))}
+            </pre>
+          )}
+        </ScrollArea>
+      </DialogContent>
+    </Dialog>
+  );
+}
@@ -0,0 +1,96 @@
+'use client';
+
+import { useCallback, useEffect, useState } from 'react';
+import { api } from '@/lib/api';
+import type { LimitScope, LockoutList } from '@/lib/types';
+import { Button } from '@/components/ui/button';
+import { Badge } from '@/components/ui/badge';
+import { Card, CardContent, CardHeader, CardTitle } from '@/components/ui/card';
+import { ScrollArea } from '@/components/ui/scroll-area';
+
+const SCOPE_LABEL: Record<LimitScope, string> = {
+  admin_ip: 'IP (admin)',
+  admin_username: 'Username admin',
+  peserta_ip: 'IP (peserta)',
+};
+
+export function LoginLockouts() {
+  const [data, setData] = useState<LockoutList | null>(null);
+
+  const refresh = useCallback(() => {
+    api.getLockouts()
+      .then((res) => {
+        if (res && Array.isArray(res.active)) {
+          setData(res);
+        }
+      })
+      .catch(() => {});
+  }, []);
+
+  useEffect(() => {
+    refresh();
+    const interval = setInterval(refresh, 5000);
+    return () => clearInterval(interval);
+  }, [refresh]);
+
+  const handleLift = async (scope: LimitScope, value: string) => {
+    await api.liftLockout(scope, value);
+    refresh();
+  };
+
+  if (!data || data.events.length === 0) return null;
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle>Lockout Login</CardTitle>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        {data.active.length > 0 && (
+          <div className="space-y-2">
+            {data.active.map((lockout) => (
+              <div
+                key={`${lockout.scope}-${lockout.value}`}
+                className="flex items-center justify-between p-3 bg-destructive/10 rounded-lg"
+              >
+                <div>
+                  <div className="font-medium">
+                    {SCOPE_LABEL[lockout.scope]}: {lockout.value}
+                  </div>
+                  <div className="text-sm text-muted-foreground">
+                    {lockout.failures} gagal, terkunci sampai{' '}
+                    {new Date(lockout.locked_until).toLocaleTimeString()}
+                  </div>
+                </div>
+                <Button
+                  size="sm"
+                  variant="outline"
+                  onClick={() => handleLift(lockout.scope, lockout.value)}
+                >
+                  Buka
+                </Button>
+              </div>
+            ))}
+          </div>
+        )}
+        <ScrollArea className="h-[200px]">
+          <div className="space-y-2">
+            {data.events.map((event, i) => (
+              <div key={i} className="flex items-center justify-between text-sm">
+                <span>
+                  {new Date(event.locked_at).toLocaleTimeString()} -{' '}
+                  {SCOPE_LABEL[event.scope]}: {event.value}
+                </span>
+                {event.lifted_by ? (
+                  <Badge variant="secondary">Dibuka oleh {event.lifted_by}</Badge>
+                ) : (
+                  <Badge variant="outline">{event.failures} gagal</Badge>
+                )}
+              </div>
+            ))}
+          </div>
+        </ScrollArea>
+      </CardContent>
+    </Card>
+  );
+}
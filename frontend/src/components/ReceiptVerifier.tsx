@@ -0,0 +1,70 @@
+'use client';
+
+import { useState } from 'react';
+import { api } from '@/lib/api';
+import type { ReceiptVerification } from '@/lib/types';
+import { Button } from '@/components/ui/button';
+import { Card, CardContent, CardHeader, CardTitle } from '@/components/ui/card';
+import { Alert, AlertDescription } from '@/components/ui/alert';
+
+// Admin menempelkan isi file bukti upload dari peserta untuk dicek keasliannya
+export function ReceiptVerifier() {
+  const [text, setText] = useState('');
+  const [result, setResult] = useState<ReceiptVerification | null>(null);
+  const [error, setError] = useState('');
+
+  const handleVerify = async () => {
+    setResult(null);
+    setError('');
+    let receipt;
+    try {
+      receipt = JSON.parse(text);
+    } catch {
+      setError('Isi bukti bukan JSON yang valid');
+      return;
+    }
+    const res = await api.verifyReceipt(receipt);
+    if (res.error) {
+      setError(res.error);
+    } else {
+      setResult(res);
+    }
+  };
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle>Verifikasi Bukti Upload</CardTitle>
+      </CardHeader>
+      <CardContent className="space-y-3">
+        <textarea
+          className="w-full h-32 rounded-md border bg-transparent p-2 font-mono text-xs"
+          placeholder="Tempel isi file bukti upload (JSON) dari peserta"
+          value={text}
+          onChange={(e) => setText(e.target.value)}
+        />
+        <Button onClick={handleVerify} disabled={!text.trim()}>
+          Verifikasi
+        </Button>
+        {error && (
+          <Alert variant="destructive">
+            <AlertDescription>{error}</AlertDescription>
+          </Alert>
+        )}
+        {result && (
+          <Alert variant={result.valid ? 'default' : 'destructive'}>
+            <AlertDescription>
+              <div className="font-medium">{result.message}</div>
+              {result.file && (
+                <div className="text-xs mt-1">
+                  {result.file.filename} v{result.file.version} • diupload{' '}
+                  {new Date(result.file.uploaded_at).toLocaleString('id-ID')}
+                </div>
+              )}
+            </AlertDescription>
+          </Alert>
+        )}
+      </CardContent>
+    </Card>
+  );
+}
@@ -0,0 +1,79 @@
+'use client';
+
+import { useState } from 'react';
+import { api } from '@/lib/api';
+import { formatSecondsLate } from '@/lib/lateness';
+import type { Meja } from '@/lib/types';
+import { Badge } from '@/components/ui/badge';
+import { Button } from '@/components/ui/button';
+import { Input } from '@/components/ui/input';
+
+// Tambahan waktu per meja; daftar ikut diperbarui lewat state WebSocket
+export function TimeExtensionControl({ meja }: { meja: Meja }) {
+  const [minutes, setMinutes] = useState('');
+  const [reason, setReason] = useState('');
+  const [error, setError] = useState('');
+
+  const totalSeconds = meja.extensions.reduce((sum, ext) => sum + ext.seconds, 0);
+
+  const handleGrant = async () => {
+    const seconds = Math.round(parseFloat(minutes) * 60);
+    if (!(seconds > 0) || !reason.trim()) {
+      setError('Isi menit dan alasan');
+      return;
+    }
+    const res = await api.grantExtension(meja.id, seconds, reason.trim());
+    if (res.success) {
+      setMinutes('');
+      setReason('');
+      setError('');
+    } else {
+      setError(res.error || 'Gagal menambah waktu');
+    }
+  };
+
+  return (
+    <div className="flex flex-col gap-1 text-xs min-w-[220px]">
+      {totalSeconds > 0 && (
+        <Badge variant="secondary" className="w-fit">
+          +{formatSecondsLate(totalSeconds)}
+        </Badge>
+      )}
+      {meja.extensions.map((ext) => (
+        <div key={ext.id} className="flex items-center justify-between gap-2">
+          <span className="text-muted-foreground">
+            +{formatSecondsLate(ext.seconds)} - {ext.reason} ({ext.granted_by})
+          </span>
+          <Button
+            variant="ghost"
+            size="sm"
+            className="h-6 px-2 text-xs"
+            onClick={() => api.revokeExtension(meja.id, ext.id)}
+          >
+            Batal
+          </Button>
+        </div>
+      ))}
+      <div className="flex items-center gap-1">
+        <Input
+          type="number"
+          min={1}
+          value={minutes}
+          placeholder="Menit"
+          className="h-7 w-16 text-xs"
+          onChange={(e) => setMinutes(e.target.value)}
+        />
+        <Input
+          value={reason}
+          placeholder="Alasan"
+          className="h-7 text-xs"
+          onChange={(e) => setReason(e.target.value)}
+        />
+        <Button variant="outline" size="sm" className="h-7 px-2 text-xs" onClick={handleGrant}>
+          Tambah
+        </Button>
+      </div>
+      {error && <span className="text-destructive">{error}</span>}
+    </div>
+  );
+}
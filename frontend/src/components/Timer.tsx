@@ -26,13 +26,16 @@ export function Timer({ timer, large = false }: TimerProps) {
       return;
     }
 
-    const startedAt = timer.started_at ? new Date(timer.started_at).getTime() : Date.now();
+    // Sisa waktu dihitung dari deadline server, bukan dari started_at,
+    // supaya jeda dan adjust tidak membuat tampilan melompat
+    const deadline = timer.deadline
+      ? new Date(timer.deadline).getTime()
+      : Date.now() + timer.remaining_seconds * 1000;
     
     // Gunakan requestAnimationFrame untuk update lebih smooth
     const updateTimer = () => {
       const now = Date.now();
-      const elapsed = Math.floor((now - startedAt) / 1000);
-      const remaining = Math.max(0, timer.duration_seconds - elapsed);
+      const remaining = Math.max(0, Math.ceil((deadline - now) / 1000));
       setDisplaySeconds(remaining);
       
       if (remaining > 0) {
@@ -49,7 +52,7 @@ export function Timer({ timer, large = false }: TimerProps) {
         animationRef.current = null;
       }
     };
-  }, [timer.is_running, timer.started_at, timer.duration_seconds, timer.remaining_seconds]);
+  }, [timer.is_running, timer.deadline, timer.remaining_seconds]);
 
   const hours = Math.floor(displaySeconds / 3600);
   const minutes = Math.floor((displaySeconds % 3600) / 60);
@@ -0,0 +1,85 @@
+'use client';
+
+import { useEffect, useState } from 'react';
+import { api } from '@/lib/api';
+import type { UploadRules } from '@/lib/types';
+import { Button } from '@/components/ui/button';
+import { Card, CardContent, CardHeader, CardTitle } from '@/components/ui/card';
+import { Input } from '@/components/ui/input';
+import { Label } from '@/components/ui/label';
+
+const MB = 1024 * 1024;
+
+// Aturan upload berlaku untuk setiap meja; kolom kosong berarti tanpa batas
+export function UploadRulesForm({ rules }: { rules: UploadRules }) {
+  const [quotaMb, setQuotaMb] = useState('');
+  const [maxFiles, setMaxFiles] = useState('');
+  const [allowedTypes, setAllowedTypes] = useState('');
+  const [message, setMessage] = useState('');
+
+  // State dari WebSocket selalu objek baru, jadi form hanya diisi ulang jika nilainya berubah
+  const typesText = rules.allowed_types.join(', ');
+  useEffect(() => {
+    setQuotaMb(rules.max_total_bytes ? String(rules.max_total_bytes / MB) : '');
+    setMaxFiles(rules.max_files ? String(rules.max_files) : '');
+    setAllowedTypes(typesText);
+  }, [rules.max_total_bytes, rules.max_files, typesText]);
+
+  const handleSave = async () => {
+    const quota = parseFloat(quotaMb);
+    const files = parseInt(maxFiles);
+    const res = await api.setUploadRules({
+      max_total_bytes: quota > 0 ? Math.round(quota * MB) : null,
+      max_files: files > 0 ? files : null,
+      allowed_types: allowedTypes.split(','),
+    });
+    setMessage(res.success ? 'Aturan upload disimpan' : res.error || 'Gagal menyimpan aturan upload');
+  };
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle>Aturan Upload</CardTitle>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        <div className="grid grid-cols-2 gap-4">
+          <div>
+            <Label>Kuota per Meja (MB)</Label>
+            <Input
+              type="number"
+              min={0}
+              value={quotaMb}
+              placeholder="Tanpa batas"
+              onChange={(e) => setQuotaMb(e.target.value)}
+            />
+          </div>
+          <div>
+            <Label>Jumlah File Maksimal</Label>
+            <Input
+              type="number"
+              min={0}
+              value={maxFiles}
+              placeholder="Tanpa batas"
+              onChange={(e) => setMaxFiles(e.target.value)}
+            />
+          </div>
+        </div>
+        <div>
+          <Label>Tipe File yang Diizinkan</Label>
+          <Input
+            value={allowedTypes}
+            placeholder=".py, .cpp, .zip, text/*"
+            onChange={(e) => setAllowedTypes(e.target.value)}
+          />
+          <p className="text-sm text-muted-foreground mt-1">
+            Ekstensi atau tipe MIME, dipisah koma. Isi file ikut diperiksa. Kosongkan untuk mengizinkan semua tipe.
+          </p>
+        </div>
+        <div className="flex items-center gap-4">
+          <Button onClick={handleSave}>Simpan</Button>
+          {message && <span className="text-sm text-muted-foreground">{message}</span>}
+        </div>
+      </CardContent>
+    </Card>
+  );
+}
@@ -0,0 +1,61 @@
+'use client';
+
+import { useCallback, useEffect, useState } from 'react';
+import { api } from '@/lib/api';
+import type { Meja, WithdrawnFile } from '@/lib/types';
+import { Badge } from '@/components/ui/badge';
+import { Card, CardContent, CardHeader, CardTitle } from '@/components/ui/card';
+import { ScrollArea } from '@/components/ui/scroll-area';
+
+// File yang dihapus atau diganti peserta; blob-nya sudah tidak ada,
+// yang tersisa hanya catatan audit dari server
+export function WithdrawnFiles({ mejaList }: { mejaList: Meja[] }) {
+  const [entries, setEntries] = useState<WithdrawnFile[]>([]);
+
+  const refresh = useCallback(() => {
+    api.getWithdrawnFiles()
+      .then((res) => {
+        if (Array.isArray(res)) {
+          setEntries(res);
+        }
+      })
+      .catch(() => {});
+  }, []);
+
+  useEffect(() => {
+    refresh();
+    const interval = setInterval(refresh, 5000);
+    return () => clearInterval(interval);
+  }, [refresh]);
+
+  if (entries.length === 0) return null;
+
+  const nomorOf = (mejaId: string) => mejaList.find((m) => m.id === mejaId)?.nomor;
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle>File Ditarik Peserta</CardTitle>
+      </CardHeader>
+      <CardContent>
+        <ScrollArea className="h-[200px]">
+          <div className="space-y-2">
+            {entries.map((entry) => (
+              <div key={`${entry.file_id}-${entry.withdrawn_at}`} className="flex items-center justify-between text-sm">
+                <span>
+                  {new Date(entry.withdrawn_at).toLocaleTimeString()} - Meja {nomorOf(entry.meja_id) ?? '?'}:{' '}
+                  {entry.filename} (v{entry.version})
+                </span>
+                {entry.action === 'replace' ? (
+                  <Badge variant="secondary">Diganti</Badge>
+                ) : (
+                  <Badge variant="outline">Dihapus</Badge>
+                )}
+              </div>
+            ))}
+          </div>
+        </ScrollArea>
+      </CardContent>
+    </Card>
+  );
+}
@@ -1,10 +1,17 @@
+import type { UploadRules } from '@/lib/types';
+
 const API_BASE = process.env.NEXT_PUBLIC_API_URL || 'http://localhost:3001';
 
 export type UploadProgressCallback = (progress: number) => void;
 
-function getAuthHeader(): HeadersInit {
+export type TokenKey = 'adminToken' | 'pesertaToken';
+
+const UPLOAD_CHUNK_SIZE = 5 * 1024 * 1024;
+const UPLOAD_MAX_RETRIES = 8;
+
+function getTokenHeader(tokenKey: TokenKey): HeadersInit {
   if (typeof window !== 'undefined') {
-    const token = localStorage.getItem('adminToken');
+    const token = localStorage.getItem(tokenKey);
     if (token) {
       return { 'Authorization': `Bearer ${token}` };
     }
@@ -12,6 +19,14 @@ function getAuthHeader(): HeadersInit {
   return {};
 }
 
+function getAuthHeader(): HeadersInit {
+  return getTokenHeader('adminToken');
+}
+
+function getPesertaAuthHeader(): HeadersInit {
+  return getTokenHeader('pesertaToken');
+}
+
 export const api = {
   // Auth
   async adminLogin(username: string, password: string) {
@@ -30,6 +45,14 @@ export const api = {
     return res.json();
   },
 
+  async logout() {
+    const res = await fetch(`${API_BASE}/api/auth/logout`, {
+      method: 'POST',
+      headers: getAuthHeader(),
+    });
+    return res.json();
+  },
+
   async changePassword(oldPassword: string, newPassword: string) {
     const res = await fetch(`${API_BASE}/api/auth/change-password`, {
       method: 'POST',
@@ -41,7 +64,9 @@ export const api = {
 
   // Admin
   async getState() {
-    const res = await fetch(`${API_BASE}/api/state`);
+    const res = await fetch(`${API_BASE}/api/state`, {
+      headers: getAuthHeader(),
+    });
     return res.json();
   },
 
@@ -68,6 +93,85 @@ export const api = {
     return res.json();
   },
 
+  async reopenMeja(mejaId: string) {
+    const res = await fetch(`${API_BASE}/api/admin/meja/${mejaId}/reopen`, {
+      method: 'POST',
+      headers: getAuthHeader(),
+    });
+    return res.json();
+  },
+
+  // Tanpa `against`, dibandingkan dengan versi sebelumnya
+  async diffMejaFile(mejaId: string, fileId: string, against?: string) {
+    const query = against ? `?against=${encodeURIComponent(against)}` : '';
+    const res = await fetch(`${API_BASE}/api/admin/meja/${mejaId}/files/${fileId}/diff${query}`, {
+      headers: getAuthHeader(),
+    });
+    return res.json();
+  },
+
+  async verifyReceipt(receipt: unknown) {
+    const res = await fetch(`${API_BASE}/api/admin/receipts/verify`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json', ...getAuthHeader() },
+      body: JSON.stringify(receipt),
+    });
+    return res.json();
+  },
+
+  async getWithdrawnFiles(mejaId?: string) {
+    const query = mejaId ? `?meja_id=${encodeURIComponent(mejaId)}` : '';
+    const res = await fetch(`${API_BASE}/api/admin/audit/files${query}`, {
+      headers: getAuthHeader(),
+    });
+    return res.json();
+  },
+
+  // ZIP di-stream langsung ke browser, jadi token ikut lewat query seperti link download
+  getSubmissionsZipUrl(options: { meja?: number; latest?: boolean } = {}) {
+    const token = typeof window !== 'undefined' ? localStorage.getItem('adminToken') : null;
+    const query = new URLSearchParams();
+    if (options.meja !== undefined) query.set('meja', String(options.meja));
+    if (options.latest) query.set('latest', 'true');
+    if (token) query.set('token', token);
+    return `${API_BASE}/api/admin/meja/export/zip?${query}`;
+  },
+
+  async setGracePeriod(graceMinutes: number) {
+    const res = await fetch(`${API_BASE}/api/admin/timer/grace`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json', ...getAuthHeader() },
+      body: JSON.stringify({ grace_minutes: graceMinutes }),
+    });
+    return res.json();
+  },
+
+  async grantExtension(mejaId: string, seconds: number, reason: string) {
+    const res = await fetch(`${API_BASE}/api/admin/meja/${mejaId}/extensions`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json', ...getAuthHeader() },
+      body: JSON.stringify({ seconds, reason }),
+    });
+    return res.json();
+  },
+
+  async revokeExtension(mejaId: string, extensionId: number) {
+    const res = await fetch(`${API_BASE}/api/admin/meja/${mejaId}/extensions/${extensionId}`, {
+      method: 'DELETE',
+      headers: getAuthHeader(),
+    });
+    return res.json();
+  },
+
+  async setUploadRules(rules: UploadRules) {
+    const res = await fetch(`${API_BASE}/api/admin/upload-rules`, {
+      method: 'PUT',
+      headers: { 'Content-Type': 'application/json', ...getAuthHeader() },
+      body: JSON.stringify(rules),
+    });
+    return res.json();
+  },
+
   async setTimer(durationMinutes: number) {
     const res = await fetch(`${API_BASE}/api/admin/timer/set`, {
       method: 'POST',
@@ -152,6 +256,22 @@ export const api = {
     return res.json();
   },
 
+  async getLockouts() {
+    const res = await fetch(`${API_BASE}/api/admin/lockouts`, {
+      headers: getAuthHeader(),
+    });
+    return res.json();
+  },
+
+  async liftLockout(scope: string, value: string) {
+    const res = await fetch(`${API_BASE}/api/admin/lockouts/lift`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json', ...getAuthHeader() },
+      body: JSON.stringify({ scope, value }),
+    });
+    return res.json();
+  },
+
   // Participant
   async login(kode: string) {
     const res = await fetch(`${API_BASE}/api/login`, {
@@ -163,20 +283,23 @@ export const api = {
   },
 
   async getMeja(id: string) {
-    const res = await fetch(`${API_BASE}/api/meja/${id}`);
+    const res = await fetch(`${API_BASE}/api/meja/${id}`, {
+      headers: getPesertaAuthHeader(),
+    });
     return res.json();
   },
 
   async updatePeserta(mejaId: string, nama: string) {
     const res = await fetch(`${API_BASE}/api/meja/${mejaId}/update`, {
       method: 'POST',
-      headers: { 'Content-Type': 'application/json' },
+      headers: { 'Content-Type': 'application/json', ...getPesertaAuthHeader() },
       body: JSON.stringify({ nama }),
     });
     return res.json();
   },
 
   uploadFileWithProgress(mejaId: string, files: FileList, onProgress?: UploadProgressCallback): Promise<{ success: boolean }> {
+    const token = typeof window !== 'undefined' ? localStorage.getItem('pesertaToken') : null;
     return new Promise((resolve, reject) => {
       const formData = new FormData();
       for (let i = 0; i < files.length; i++) {
@@ -185,6 +308,9 @@ export const api = {
       
       const xhr = new XMLHttpRequest();
       xhr.open('POST', `${API_BASE}/api/meja/${mejaId}/upload`);
+      if (token) {
+        xhr.setRequestHeader('Authorization', `Bearer ${token}`);
+      }
       
       xhr.upload.onprogress = (event) => {
         if (event.lengthComputable && onProgress) {
@@ -211,6 +337,119 @@ export const api = {
     });
   },
 
+  async submitMeja(mejaId: string) {
+    const res = await fetch(`${API_BASE}/api/meja/${mejaId}/submit`, {
+      method: 'POST',
+      headers: getPesertaAuthHeader(),
+    });
+    return res.json();
+  },
+
+  async markFileFinal(mejaId: string, fileId: string) {
+    const res = await fetch(`${API_BASE}/api/meja/${mejaId}/files/${fileId}/final`, {
+      method: 'POST',
+      headers: getPesertaAuthHeader(),
+    });
+    return res.json();
+  },
+
+  async deleteMejaFile(mejaId: string, fileId: string) {
+    const res = await fetch(`${API_BASE}/api/meja/${mejaId}/files/${fileId}`, {
+      method: 'DELETE',
+      headers: getPesertaAuthHeader(),
+    });
+    return res.json();
+  },
+
+  // File lama tetap tercatat di audit admin sebagai "diganti"
+  async replaceMejaFile(mejaId: string, fileId: string, file: File) {
+    const formData = new FormData();
+    formData.append('file', file);
+    const res = await fetch(`${API_BASE}/api/meja/${mejaId}/files/${fileId}`, {
+      method: 'PUT',
+      headers: getPesertaAuthHeader(),
+      body: formData,
+    });
+    return res.json();
+  },
+
+  async getFileReceipt(mejaId: string, fileId: string) {
+    const res = await fetch(`${API_BASE}/api/meja/${mejaId}/files/${fileId}/receipt`, {
+      headers: getPesertaAuthHeader(),
+    });
+    return res.json();
+  },
+
+  // Upload bertahap: file dikirim per potongan dan dilanjutkan dari offset
+  // terakhir di server jika koneksi putus, termasuk setelah halaman dimuat ulang
+  async uploadFileResumable(mejaId: string, file: File, onProgress?: UploadProgressCallback) {
+    const headers = getPesertaAuthHeader();
+    const base = `${API_BASE}/api/meja/${mejaId}/uploads`;
+    const resumeKey = `upload:${mejaId}:${file.name}:${file.size}:${file.lastModified}`;
+
+    const fail = async (res: Response) => {
+      const body = await res.json().catch(() => ({}));
+      return new Error(body.error || 'Upload failed');
+    };
+
+    let uploadId = localStorage.getItem(resumeKey);
+    let offset = 0;
+    if (uploadId) {
+      const res = await fetch(`${base}/${uploadId}`, { headers });
+      if (res.ok) {
+        offset = (await res.json()).offset;
+      } else {
+        uploadId = null;
+      }
+    }
+    if (!uploadId) {
+      const res = await fetch(base, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json', ...headers },
+        body: JSON.stringify({ filename: file.name, size: file.size }),
+      });
+      if (!res.ok) throw await fail(res);
+      uploadId = (await res.json()).id as string;
+      localStorage.setItem(resumeKey, uploadId);
+    }
+
+    let retries = 0;
+    while (offset < file.size) {
+      onProgress?.(Math.round((offset / file.size) * 100));
+      try {
+        const res = await fetch(`${base}/${uploadId}`, {
+          method: 'PATCH',
+          headers: { 'Upload-Offset': String(offset), ...headers },
+          body: file.slice(offset, offset + UPLOAD_CHUNK_SIZE),
+        });
+        const body = await res.json().catch(() => ({}));
+        // 409 dengan offset: offset server berbeda, lanjutkan dari offset server
+        if ((res.ok || res.status === 409) && typeof body.offset === 'number') {
+          offset = body.offset;
+          retries = 0;
+          continue;
+        }
+        if (res.status < 500 && res.status !== 409) {
+          localStorage.removeItem(resumeKey);
+          throw new Error(body.error || 'Upload failed');
+        }
+      } catch (e) {
+        if (!(e instanceof TypeError)) throw e;
+      }
+      // Gangguan jaringan atau server: tunggu lalu tanyakan offset terakhir
+      if (++retries > UPLOAD_MAX_RETRIES) throw new Error('Upload gagal, koneksi terputus');
+      await new Promise((r) => setTimeout(r, Math.min(1000 * 2 ** retries, 15000)));
+      const res = await fetch(`${base}/${uploadId}`, { headers }).catch(() => null);
+      if (res?.ok) offset = (await res.json()).offset;
+    }
+
+    const res = await fetch(`${base}/${uploadId}/finalize`, { method: 'POST', headers });
+    if (!res.ok) throw await fail(res);
+    localStorage.removeItem(resumeKey);
+    onProgress?.(100);
+    return res.json();
+  },
+
   async getSoalList() {
     const res = await fetch(`${API_BASE}/api/soal`);
     return res.json();
@@ -220,22 +459,49 @@ export const api = {
     return `${API_BASE}/api/soal/${id}/download`;
   },
 
-  getFileDownloadUrl(path: string) {
-    return `${API_BASE}/storage/${path.replace(/\\/g, '/').replace('./storage/', '')}`;
+  // Link download tidak bisa membawa header Authorization, jadi token dikirim lewat query
+  getFileDownloadUrl(mejaId: string, fileId: string, tokenKey: TokenKey) {
+    const token = typeof window !== 'undefined' ? localStorage.getItem(tokenKey) : null;
+    const query = token ? `?token=${encodeURIComponent(token)}` : '';
+    return `${API_BASE}/api/meja/${mejaId}/files/${fileId}/download${query}`;
   },
 
-  async previewArchive(path: string) {
-    const res = await fetch(`${API_BASE}/api/archive/preview?path=${encodeURIComponent(path)}`);
+  async previewArchive(mejaId: string, fileId: string, tokenKey: TokenKey) {
+    const res = await fetch(`${API_BASE}/api/archive/preview/${mejaId}/${fileId}`, {
+      headers: getTokenHeader(tokenKey),
+    });
     return res.json();
   },
 
-  async previewFile(path: string) {
-    const res = await fetch(`${API_BASE}/api/file/preview?path=${encodeURIComponent(path)}`);
+  // Entry biner dikirim sebagai file mentah, bukan JSON; null berarti harus diunduh
+  async previewArchiveEntry(mejaId: string, fileId: string, name: string, tokenKey: TokenKey) {
+    const res = await fetch(
+      `${API_BASE}/api/archive/entry/${mejaId}/${fileId}?name=${encodeURIComponent(name)}`,
+      { headers: getTokenHeader(tokenKey) },
+    );
+    if (!res.headers.get('content-type')?.includes('application/json')) {
+      await res.body?.cancel();
+      return null;
+    }
+    return res.json();
+  },
+
+  getArchiveEntryDownloadUrl(mejaId: string, fileId: string, name: string, tokenKey: TokenKey) {
+    const token = typeof window !== 'undefined' ? localStorage.getItem(tokenKey) : null;
+    const query = new URLSearchParams({ name, download: 'true' });
+    if (token) query.set('token', token);
+    return `${API_BASE}/api/archive/entry/${mejaId}/${fileId}?${query}`;
+  },
+
+  async previewFile(mejaId: string, fileId: string, tokenKey: TokenKey) {
+    const res = await fetch(`${API_BASE}/api/file/preview/${mejaId}/${fileId}`, {
+      headers: getTokenHeader(tokenKey),
+    });
     return res.json();
   },
 
-  getWsUrl() {
+  getWsUrl(token?: string | null) {
     const wsBase = API_BASE.replace('http', 'ws');
-    return `${wsBase}/ws`;
+    return token ? `${wsBase}/ws?token=${encodeURIComponent(token)}` : `${wsBase}/ws`;
   },
 };
@@ -0,0 +1,23 @@
+import type { TimerState } from './types';
+
+function hasEnded(timer: TimerState) {
+  return timer.duration_seconds > 0 && timer.remaining_seconds <= 0;
+}
+
+// Sisa masa tenggang dalam detik; 0 jika timer belum habis atau tenggang sudah lewat.
+// Sama seperti server, timer yang habis tanpa `ended_at` dianggap sudah lama habis.
+export function graceRemaining(timer: TimerState, now: number = Date.now()) {
+  if (!hasEnded(timer) || !timer.ended_at) return 0;
+  const graceEnd = new Date(timer.ended_at).getTime() + timer.grace_seconds * 1000;
+  return Math.max(0, Math.ceil((graceEnd - now) / 1000));
+}
+
+// Timer habis dan masa tenggang sudah lewat: upload ditolak server
+export function isUploadClosed(timer: TimerState, now: number = Date.now()) {
+  return hasEnded(timer) && graceRemaining(timer, now) <= 0;
+}
+
+export function formatSecondsLate(seconds: number) {
+  const minutes = Math.floor(seconds / 60);
+  return minutes > 0 ? `${minutes}m ${seconds % 60}s` : `${seconds}s`;
+}
@@ -1,10 +1,22 @@
 export interface Meja {
   id: string;
   nomor: number;
-  kode: string;
+  // Tidak dikirim untuk admin yang tidak boleh mengelola meja
+  kode?: string;
   nama_peserta: string | null;
   files: FileInfo[];
   last_upload: string | null;
+  submitted_at: string | null;
+  extensions: TimeExtension[];
+}
+
+// Tambahan waktu khusus meja; deadline meja = akhir timer + total tambahan
+export interface TimeExtension {
+  id: number;
+  seconds: number;
+  reason: string;
+  granted_by: string;
+  granted_at: string;
 }
 
 export interface FileInfo {
@@ -13,6 +25,50 @@ export interface FileInfo {
   size: number;
   uploaded_at: string;
   path: string;
+  version: number;
+  is_final: boolean;
+  sha256: string | null;
+  late: boolean;
+  seconds_late: number | null;
+}
+
+export interface Receipt {
+  file_id: string;
+  meja_id: string;
+  meja_nomor: number;
+  filename: string;
+  version: number;
+  size: number;
+  sha256: string;
+  uploaded_at: string;
+  signature: string;
+}
+
+export interface WithdrawnFile {
+  meja_id: string;
+  file_id: string;
+  filename: string;
+  version: number;
+  size: number;
+  sha256?: string | null;
+  uploaded_at: string;
+  action: 'delete' | 'replace';
+  replaced_by?: string | null;
+  withdrawn_at: string;
+}
+
+export interface ReceiptVerification {
+  valid: boolean;
+  signature_valid: boolean;
+  message: string;
+  file?: FileInfo | null;
+}
+
+export interface FileDiff {
+  from: { id: string; filename: string; version: number };
+  to: { id: string; filename: string; version: number };
+  identical: boolean;
+  diff: string;
 }
 
 export interface TimerState {
@@ -20,6 +76,12 @@ export interface TimerState {
   duration_seconds: number;
   remaining_seconds: number;
   started_at: string | null;
+  // Saat timer mencapai nol jika terus berjalan; digeser oleh jeda dan adjust
+  deadline: string | null;
+  paused_at: string | null;
+  paused_seconds: number;
+  ended_at: string | null;
+  grace_seconds: number;
 }
 
 export interface SoalFile {
@@ -29,21 +91,67 @@ export interface SoalFile {
   uploaded_at: string;
 }
 
+export interface MejaProgress {
+  nomor: number;
+  jumlah_file: number;
+  last_upload: string | null;
+}
+
 export interface AppState {
   meja_list: Record<string, Meja>;
+  progress: MejaProgress[];
   timer: TimerState;
   soal_files: SoalFile[];
   lomba_title: string;
+  upload_rules: UploadRules;
+}
+
+// Kosong/null berarti tidak dibatasi
+export interface UploadRules {
+  max_total_bytes: number | null;
+  max_files: number | null;
+  allowed_types: string[];
 }
 
+export type UploadErrorCode =
+  | 'file_too_large'
+  | 'upload_interrupted'
+  | 'storage_error'
+  | 'too_many_files'
+  | 'quota_exceeded'
+  | 'type_not_allowed';
+
+export type ArchiveFormat = 'zip' | 'rar' | 'tar' | 'tar_gz' | 'tar_xz' | 'seven_z';
+
 export interface ArchiveEntry {
   name: string;
   size: number;
+  compressed_size: number | null;
+  modified: string | null;
   is_dir: boolean;
+  children: ArchiveEntry[] | null;
+}
+
+export type ArchiveWarningKind =
+  | 'too_many_entries'
+  | 'total_size_exceeded'
+  | 'suspicious_ratio'
+  | 'path_too_deep'
+  | 'unsafe_path'
+  | 'nesting_too_deep'
+  | 'nested_unreadable';
+
+export interface ArchiveWarning {
+  kind: ArchiveWarningKind;
+  entry: string | null;
+  message: string;
 }
 
 export interface ArchiveContent {
+  format: ArchiveFormat | null;
   files: ArchiveEntry[];
+  encrypted: boolean;
+  warnings: ArchiveWarning[];
 }
 
 export interface FilePreview {
@@ -52,3 +160,26 @@ export interface FilePreview {
   is_text: boolean;
   size: number;
 }
+
+export type LimitScope = 'admin_ip' | 'admin_username' | 'peserta_ip';
+
+export interface Lockout {
+  scope: LimitScope;
+  value: string;
+  failures: number;
+  locked_until: string;
+}
+
+export interface LockoutEvent {
+  scope: LimitScope;
+  value: string;
+  failures: number;
+  locked_at: string;
+  locked_until: string;
+  lifted_by: string | null;
+}
+
+export interface LockoutList {
+  active: Lockout[];
+  events: LockoutEvent[];
+}
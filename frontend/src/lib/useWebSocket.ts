@@ -4,7 +4,9 @@ import { useEffect, useRef, useState, useCallback } from 'react';
 import { api } from './api';
 import type { AppState } from './types';
 
-export function useWebSocket() {
+// tokenKey: key localStorage berisi token (adminToken / pesertaToken).
+// sessionKey: ubah nilainya untuk reconnect setelah login/logout.
+export function useWebSocket(tokenKey?: string, sessionKey?: unknown) {
   const [state, setState] = useState<AppState | null>(null);
   const [connected, setConnected] = useState(false);
   const wsRef = useRef<WebSocket | null>(null);
@@ -14,7 +16,8 @@ export function useWebSocket() {
     if (wsRef.current?.readyState === WebSocket.OPEN) return;
 
     try {
-      const ws = new WebSocket(api.getWsUrl());
+      const token = tokenKey ? localStorage.getItem(tokenKey) : null;
+      const ws = new WebSocket(api.getWsUrl(token));
 
       ws.onopen = () => {
         setConnected(true);
@@ -48,7 +51,7 @@ export function useWebSocket() {
       console.error('Failed to connect WebSocket:', e);
       reconnectTimeoutRef.current = setTimeout(connect, 2000);
     }
-  }, []);
+  }, [tokenKey]);
 
   useEffect(() => {
     connect();
@@ -58,10 +61,12 @@ export function useWebSocket() {
         clearTimeout(reconnectTimeoutRef.current);
       }
       if (wsRef.current) {
+        wsRef.current.onclose = null;
         wsRef.current.close();
+        wsRef.current = null;
       }
     };
-  }, [connect]);
+  }, [connect, sessionKey]);
 
   return { state, connected };
 }
@@ -0,0 +1,16 @@
+import type { FileInfo } from './types';
+
+// Versi yang dinilai: yang ditandai final, atau versi terakhir jika belum ada
+export function isSubmittedVersion(files: FileInfo[], file: FileInfo) {
+  const versions = files.filter((f) => f.filename === file.filename);
+  const final = versions.find((f) => f.is_final);
+  if (final) return final.id === file.id;
+  return Math.max(...versions.map((f) => f.version)) === file.version;
+}
+
+// Urut per nama file, versi terbaru di atas
+export function sortByVersion(files: FileInfo[]) {
+  return [...files].sort(
+    (a, b) => a.filename.localeCompare(b.filename) || b.version - a.version
+  );
+}